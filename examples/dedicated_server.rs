@@ -0,0 +1,25 @@
+use spaghetti_engine::core::Game;
+use spaghetti_engine::log;
+use spaghetti_engine::settings::GameSettings;
+
+// A dedicated server needs no window, so there's no need for the entry point
+fn main() {
+    // Deployments configure the server with --set name=value or SPAGHETTI_* variables
+    let settings = GameSettings::new();
+    if let Err(error) = settings.apply_process_overrides() {
+        log!(Fatal, &error, "Invalid settings");
+        return;
+    }
+
+    let game = Game::builder()
+        .with_name("server")
+        .with_settings(settings)
+        .headless()
+        .stop_on_interrupt()
+        .build();
+
+    log!(Info, "Dedicated server started, press Ctrl+C to stop");
+    if let Err(error) = game.run() {
+        log!(Fatal, &error, "The server failed");
+    }
+}
@@ -1,7 +1,22 @@
-use spaghetti_engine::spaghetti_entry_point;
+use spaghetti_engine::core::{entry_point, Game};
+use spaghetti_engine::{log, spaghetti_entry_point};
+use std::time::Duration;
 
 fn main() {
     spaghetti_entry_point!(demo());
 }
 
-fn demo() {}
+fn demo() {
+    let game = Game::builder().as_client().build();
+
+    // Stop the game after a few seconds
+    let stopper = game.clone();
+    entry_point::register_coroutine(|ctx| async move {
+        ctx.wait(Duration::from_secs(3)).await;
+        stopper.stop();
+    });
+
+    if let Err(error) = game.run() {
+        log!(Fatal, &error, "The game failed");
+    }
+}
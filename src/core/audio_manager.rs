@@ -0,0 +1,29 @@
+use crate::core::ThreadComponent;
+use std::thread;
+use std::time::Duration;
+
+static SLEEP_DURATION: Duration = Duration::from_millis(1);
+
+/// Plays game sounds on the client
+pub struct AudioManager {}
+
+impl AudioManager {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ThreadComponent for AudioManager {
+    fn initialize(&mut self) {}
+
+    fn post_initialize(&mut self) {}
+
+    fn loop_cycle(&mut self, _delta: f32) {
+        // TODO AUDIO
+        thread::sleep(SLEEP_DURATION);
+    }
+
+    fn pre_terminate(&mut self) {}
+
+    fn terminate(&mut self) {}
+}
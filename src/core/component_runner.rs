@@ -0,0 +1,346 @@
+use crate::core::{Game, ThreadComponent};
+use crate::log;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use std::{sync, thread};
+
+static DEFAULT_STOP_TIMEOUT: u64 = 10000; // 10 s
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Phase {
+    Initialize,
+    PostInitialize,
+    Loop,
+    PreTerminate,
+    Terminate,
+}
+
+// Sent back to the runner every time a component completes a phase,
+// or with no phase if the component thread panicked
+type Ack = (usize, Option<Phase>);
+
+struct PanicGuard {
+    index: usize,
+    acks: Sender<Ack>,
+    game: sync::Weak<Game>,
+}
+
+impl Drop for PanicGuard {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            let _ = self.acks.send((self.index, None));
+
+            // The game can't go on without the component
+            if let Some(game) = self.game.upgrade() {
+                game.stop();
+            }
+        }
+    }
+}
+
+struct ComponentThread {
+    name: String,
+    commands: Sender<Phase>,
+    handle: Option<JoinHandle<()>>,
+    last_sent: Option<Phase>,
+    last_ack: Option<Phase>,
+    dead: bool,
+}
+
+/// Runs a set of [`ThreadComponent`]s, each one on its own named thread.
+///
+/// Initialization hooks are called in lockstep: no component enters a phase
+/// until every component has completed the previous one.
+/// Termination hooks are called one component at a time, in reverse order
+pub struct ComponentRunner {
+    game: sync::Weak<Game>,
+    components: Vec<(String, Box<dyn ThreadComponent>)>,
+    threads: Vec<ComponentThread>,
+    stop_loop: Arc<AtomicBool>,
+    acks: Option<Receiver<Ack>>,
+}
+
+impl ComponentRunner {
+    /// Creates a runner for the given game.
+    ///
+    /// Every component thread is linked to the game for its whole lifetime
+    pub fn new(game: sync::Weak<Game>) -> Self {
+        Self {
+            game,
+            components: Vec::new(),
+            threads: Vec::new(),
+            stop_loop: Arc::new(AtomicBool::new(false)),
+            acks: None,
+        }
+    }
+
+    /// Adds a component to the runner. Has no effect once the runner is started
+    ///
+    /// # Arguments
+    /// * `name` - The name of the thread the component will run on
+    /// * `component` - The component
+    pub fn add_component(&mut self, name: &str, component: Box<dyn ThreadComponent>) {
+        if self.acks.is_some() {
+            log!(Warning, "Tried to add component {} to a running runner", name);
+            return;
+        }
+        self.components.push((name.to_string(), component));
+    }
+
+    /// Spawns the component threads and brings every component
+    /// through initialization, then lets them loop
+    ///
+    /// # Returns
+    /// * `false` if any component failed to start, in which case
+    /// the runner should be stopped
+    pub fn start(&mut self) -> bool {
+        if self.acks.is_some() {
+            log!(Warning, "Tried to start a runner multiple times");
+            return false;
+        }
+        let (ack_sender, ack_receiver) = channel();
+        self.acks = Some(ack_receiver);
+        self.stop_loop.store(false, Ordering::SeqCst);
+
+        for (index, (name, component)) in self.components.drain(..).enumerate() {
+            let (command_sender, command_receiver) = channel();
+            let stop_loop = self.stop_loop.clone();
+            let acks = ack_sender.clone();
+            let game = self.game.clone();
+
+            let handle = Game::spawn_linked_thread(&self.game, &name, move || {
+                component_thread(index, component, stop_loop, command_receiver, acks, game);
+            });
+
+            let handle = match handle {
+                Ok(handle) => Some(handle),
+                Err(error) => {
+                    log!(Fatal, &error, "Couldn't spawn thread for component {}", name);
+                    None
+                }
+            };
+
+            self.threads.push(ComponentThread {
+                dead: handle.is_none(),
+                name,
+                commands: command_sender,
+                handle,
+                last_sent: None,
+                last_ack: None,
+            });
+        }
+
+        let all: Vec<usize> = (0..self.threads.len()).collect();
+        for phase in [Phase::Initialize, Phase::PostInitialize] {
+            for index in all.iter() {
+                self.send_phase(*index, phase);
+            }
+            if !self.await_phase(&all, phase, None) {
+                return false;
+            }
+        }
+
+        for index in all.iter() {
+            self.send_phase(*index, Phase::Loop);
+        }
+        true
+    }
+
+    /// Stops every component loop, then terminates the components in
+    /// reverse order. Components that don't respond within the
+    /// `handler.stopTimeout` setting are abandoned, and joined in the background
+    ///
+    /// # Returns
+    /// * The names of the components that panicked or didn't terminate in time
+    pub fn stop(&mut self) -> Vec<String> {
+        if self.acks.is_none() {
+            return Vec::new();
+        }
+        let timeout = match self.game.upgrade() {
+            Some(game) => game
+                .get_settings()
+                .get("handler.stopTimeout")
+                .as_unsigned_int_or(DEFAULT_STOP_TIMEOUT),
+            None => DEFAULT_STOP_TIMEOUT,
+        };
+        let deadline = Instant::now() + Duration::from_millis(timeout);
+
+        // Every loop is interrupted at the same time
+        self.stop_loop.store(true, Ordering::SeqCst);
+        let looping: Vec<usize> = (0..self.threads.len())
+            .filter(|index| self.threads[*index].last_sent == Some(Phase::Loop))
+            .collect();
+        self.await_phase(&looping, Phase::Loop, Some(deadline));
+
+        for phase in [Phase::PreTerminate, Phase::Terminate] {
+            for index in (0..self.threads.len()).rev() {
+                if self.send_phase(index, phase) {
+                    self.await_phase(&[index], phase, Some(deadline));
+                }
+            }
+        }
+
+        let mut failed = Vec::new();
+        let mut abandoned = Vec::new();
+        for thread in self.threads.iter_mut() {
+            let terminated = thread.last_ack == Some(Phase::Terminate);
+            if thread.dead || !terminated {
+                failed.push(thread.name.clone());
+            }
+
+            if let Some(handle) = thread.handle.take() {
+                // Dead components are done unwinding, or about to be
+                if thread.dead || terminated {
+                    let _ = handle.join();
+                } else {
+                    log!(
+                        Error,
+                        "Component {} did not terminate in time, abandoning it",
+                        thread.name
+                    );
+                    abandoned.push((thread.name.clone(), handle));
+                }
+            }
+        }
+        self.threads.clear();
+        self.acks = None;
+
+        if !abandoned.is_empty() {
+            join_abandoned(abandoned);
+        }
+        failed
+    }
+
+    fn send_phase(&mut self, index: usize, phase: Phase) -> bool {
+        let thread = &mut self.threads[index];
+        if thread.dead {
+            return false;
+        }
+        if thread.commands.send(phase).is_err() {
+            thread.dead = true;
+            return false;
+        }
+        thread.last_sent = Some(phase);
+        true
+    }
+
+    // Waits until all the given components have acknowledged the phase,
+    // or have died, or the deadline has passed
+    fn await_phase(&mut self, indices: &[usize], phase: Phase, deadline: Option<Instant>) -> bool {
+        let acks = match &self.acks {
+            Some(acks) => acks,
+            None => return false,
+        };
+
+        loop {
+            let mut success = true;
+            let mut pending = false;
+            for index in indices.iter() {
+                let thread = &self.threads[*index];
+                if thread.dead {
+                    success = false;
+                } else if thread.last_ack != Some(phase) {
+                    pending = true;
+                }
+            }
+            if !pending {
+                return success;
+            }
+
+            let ack = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return false;
+                    }
+                    match acks.recv_timeout(deadline - now) {
+                        Ok(ack) => ack,
+                        Err(RecvTimeoutError::Timeout) => return false,
+                        Err(RecvTimeoutError::Disconnected) => return false,
+                    }
+                }
+                None => match acks.recv() {
+                    Ok(ack) => ack,
+                    Err(_) => return false,
+                },
+            };
+
+            let thread = &mut self.threads[ack.0];
+            match ack.1 {
+                Some(acked) => thread.last_ack = Some(acked),
+                None => {
+                    log!(Error, "Component {} panicked", thread.name);
+                    thread.dead = true;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ComponentRunner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// Waits for abandoned components on another thread, reporting them once they finish
+fn join_abandoned(abandoned: Vec<(String, JoinHandle<()>)>) {
+    let result = thread::Builder::new()
+        .name("abandoned_components".to_string())
+        .spawn(move || {
+            for (name, handle) in abandoned {
+                let panicked = handle.join().is_err();
+                log!(
+                    Warning,
+                    "Abandoned component {} finally terminated{}",
+                    name,
+                    if panicked { " by panicking" } else { "" }
+                );
+            }
+        });
+
+    if let Err(error) = result {
+        log!(Error, &error, "Couldn't spawn thread joining abandoned components");
+    }
+}
+
+fn component_thread(
+    index: usize,
+    mut component: Box<dyn ThreadComponent>,
+    stop_loop: Arc<AtomicBool>,
+    commands: Receiver<Phase>,
+    acks: Sender<Ack>,
+    game: sync::Weak<Game>,
+) {
+    let _guard = PanicGuard {
+        index,
+        acks: acks.clone(),
+        game,
+    };
+    while let Ok(phase) = commands.recv() {
+        match phase {
+            Phase::Initialize => component.initialize(),
+            Phase::PostInitialize => component.post_initialize(),
+            Phase::Loop => {
+                let mut last_cycle = Instant::now();
+                while !stop_loop.load(Ordering::SeqCst) {
+                    let now = Instant::now();
+                    let delta = now.duration_since(last_cycle).as_secs_f32();
+                    last_cycle = now;
+
+                    component.loop_cycle(delta);
+                }
+            }
+            Phase::PreTerminate => component.pre_terminate(),
+            Phase::Terminate => component.terminate(),
+        }
+
+        let _ = acks.send((index, Some(phase)));
+        if phase == Phase::Terminate {
+            break;
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::core::Game;
+use crate::events::GameEvent;
+use crate::utils::id_type::id_type;
+use genawaiter::sync::{Co, Gen, GenBoxed};
+use genawaiter::GeneratorState;
+use std::future::Future;
+use std::hash::Hasher;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+id_type!(CoroutineHandle);
+
+/// What a coroutine is waiting for before it can be resumed
+pub enum WaitCondition {
+    Ticks(u64),
+    Time(Duration),
+    Until(Box<dyn FnMut() -> bool + Send>),
+}
+
+/// Passed to the body of every coroutine, allows it to suspend itself
+pub struct CoroutineContext {
+    co: Co<WaitCondition>,
+}
+
+impl CoroutineContext {
+    /// Suspends the coroutine until the next tick
+    pub async fn next_tick(&self) {
+        self.co.yield_(WaitCondition::Ticks(1)).await;
+    }
+
+    /// Suspends the coroutine for the given amount of ticks
+    pub async fn wait_ticks(&self, ticks: u64) {
+        self.co.yield_(WaitCondition::Ticks(ticks)).await;
+    }
+
+    /// Suspends the coroutine for the given amount of time
+    pub async fn wait(&self, duration: Duration) {
+        self.co.yield_(WaitCondition::Time(duration)).await;
+    }
+
+    /// Suspends the coroutine until the given condition is true.
+    ///
+    /// The condition is checked once per tick
+    pub async fn wait_until<F>(&self, condition: F)
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        self.co
+            .yield_(WaitCondition::Until(Box::new(condition)))
+            .await;
+    }
+
+    /// Suspends the coroutine until an event of the given type
+    /// is dispatched by the given game
+    pub async fn wait_for_event<T: GameEvent + 'static>(&self, game: &Arc<Game>) {
+        let raised = Arc::new(AtomicBool::new(false));
+        let flag = raised.clone();
+        // The listener goes away with the coroutine, even if it never resumes
+        let guard = game
+            .get_event_dispatcher()
+            .listen_guarded(move |_: &mut T| {
+                flag.store(true, Ordering::SeqCst);
+            });
+
+        // Unregistered event type, nothing to wait for
+        if guard.is_none() {
+            return;
+        }
+
+        self.wait_until(move || raised.load(Ordering::SeqCst)).await;
+    }
+}
+
+struct CoroutineEntry {
+    handle: CoroutineHandle,
+    generator: GenBoxed<WaitCondition>,
+    waiting: Option<WaitCondition>,
+}
+
+/// A set of coroutines resumed together, usually once per tick
+pub struct Coroutines {
+    entries: Vec<CoroutineEntry>,
+}
+
+impl Coroutines {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Starts a new coroutine. Its body will first run on the next resume
+    ///
+    /// # Arguments
+    /// * `producer` - Creates the body of the coroutine from its context
+    ///
+    /// # Returns
+    /// * The handle to cancel the coroutine with
+    pub fn start<F, R>(&mut self, producer: F) -> CoroutineHandle
+    where
+        F: FnOnce(CoroutineContext) -> R,
+        R: Future<Output = ()> + Send + 'static,
+    {
+        let handle = CoroutineHandle::new();
+        self.entries.push(CoroutineEntry {
+            handle,
+            generator: Gen::new_boxed(|co| producer(CoroutineContext { co })),
+            waiting: None,
+        });
+        handle
+    }
+
+    /// Stops a coroutine, it will not be resumed anymore
+    ///
+    /// # Returns
+    /// * Whether the coroutine was found
+    pub fn cancel(&mut self, handle: CoroutineHandle) -> bool {
+        let count = self.entries.len();
+        self.entries.retain(|entry| entry.handle != handle);
+        count != self.entries.len()
+    }
+
+    pub fn is_running(&self, handle: CoroutineHandle) -> bool {
+        self.entries.iter().any(|entry| entry.handle == handle)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Moves every coroutine of `other` into this set
+    pub fn append(&mut self, other: &mut Coroutines) {
+        self.entries.append(&mut other.entries);
+    }
+
+    /// Resumes every coroutine whose wait condition is satisfied,
+    /// and removes the ones that have completed
+    ///
+    /// # Arguments
+    /// * `delta` - The time elapsed since the last resume
+    pub fn resume(&mut self, delta: Duration) {
+        self.entries.retain_mut(|entry| {
+            let ready = match &mut entry.waiting {
+                None => true,
+                Some(WaitCondition::Ticks(ticks)) => {
+                    *ticks = ticks.saturating_sub(1);
+                    *ticks == 0
+                }
+                Some(WaitCondition::Time(time)) => {
+                    *time = time.saturating_sub(delta);
+                    time.is_zero()
+                }
+                Some(WaitCondition::Until(condition)) => condition(),
+            };
+
+            if !ready {
+                return true;
+            }
+
+            match entry.generator.resume() {
+                GeneratorState::Yielded(condition) => {
+                    entry.waiting = Some(condition);
+                    true
+                }
+                GeneratorState::Complete(_) => false,
+            }
+        });
+    }
+}
@@ -1,42 +1,103 @@
+use crate::core::coroutine::{CoroutineContext, CoroutineHandle, Coroutines};
+use crate::core::job_system;
+use crate::log;
 use crate::utils::id_type::id_type;
+use crate::utils::panics;
+use crate::utils::PanicPolicy;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
-use std::ffi::c_void;
-use std::sync::Mutex;
-use std::time::{Duration, SystemTime};
-use std::{mem, thread};
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+use std::{panic, thread};
 
-struct Void {
-    _ptr: *const c_void,
+type MainThreadEvent = Box<dyn FnOnce() + Send>;
+
+struct EventQueue {
+    sender: Mutex<Sender<MainThreadEvent>>,
+    receiver: Mutex<Receiver<MainThreadEvent>>,
 }
 
-unsafe impl Send for Void {}
-unsafe impl Sync for Void {}
+impl EventQueue {
+    fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            sender: Mutex::new(sender),
+            receiver: Mutex::new(receiver),
+        }
+    }
+}
 
-static TASK_LIST: Lazy<Mutex<HashMap<TaskHandle, Box<dyn FnMut() + Sync + Send>>>> =
-    Lazy::new(|| Mutex::new(HashMap::new()));
+struct TaskEntry {
+    // Taken out while the task runs
+    task: Option<Box<dyn FnMut() + Sync + Send>>,
+    panic_policy: PanicPolicy,
+}
 
-static EVENT_LIST: Lazy<Mutex<HashMap<EventHandle, Box<dyn FnOnce() -> Void + Send + Sync>>>> =
-    Lazy::new(|| Mutex::new(HashMap::new()));
+/// Describes a task that panicked while being run by the main thread
+#[derive(Clone)]
+pub struct TaskFailure {
+    pub handle: TaskHandle,
+    pub message: String,
+    pub unregistered: bool,
+}
 
-static EVENT_RETURN: Lazy<Mutex<HashMap<EventHandle, Void>>> =
+static TASK_LIST: Lazy<Mutex<HashMap<TaskHandle, TaskEntry>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+static FAILED_TASKS: Mutex<Vec<TaskFailure>> = Mutex::new(Vec::new());
+
+static EVENT_QUEUE: Lazy<EventQueue> = Lazy::new(EventQueue::new);
+
 static SHUTDOWN_EVENT_LIST: Lazy<Mutex<Vec<Box<dyn FnOnce() + Send + Sync>>>> =
     Lazy::new(|| Mutex::new(Vec::new()));
 
+// Coroutines are started and cancelled through separate lists, so that
+// coroutine bodies can do both while the running ones are locked
+static COROUTINES: Lazy<Mutex<Coroutines>> = Lazy::new(|| Mutex::new(Coroutines::new()));
+static NEW_COROUTINES: Lazy<Mutex<Coroutines>> = Lazy::new(|| Mutex::new(Coroutines::new()));
+static CANCELLED_COROUTINES: Mutex<Vec<CoroutineHandle>> = Mutex::new(Vec::new());
+static LAST_RESUME: Mutex<Option<Instant>> = Mutex::new(None);
+
+static MAIN_THREAD: Mutex<Option<ThreadId>> = Mutex::new(None);
+
 static SLEEP_DURATION: Duration = Duration::from_millis(1);
 
 id_type!(TaskHandle);
-id_type!(EventHandle);
 
+/// Registers a task the main thread will run once per loop.
+///
+/// If the task panics, it is unregistered
 pub fn register_task<T>(f: T) -> TaskHandle
+where
+    T: FnMut() + Sync + Send + 'static,
+{
+    register_task_with_policy(f, PanicPolicy::Unregister)
+}
+
+/// Registers a task the main thread will run once per loop
+///
+/// # Arguments
+/// * `f` - The task
+/// * `panic_policy` - What to do with the task if it panics
+pub fn register_task_with_policy<T>(f: T, panic_policy: PanicPolicy) -> TaskHandle
 where
     T: FnMut() + Sync + Send + 'static,
 {
     let mut list = TASK_LIST.lock().unwrap();
     let handle = TaskHandle::new();
-    list.insert(handle, Box::new(f));
+    list.insert(
+        handle,
+        TaskEntry {
+            task: Some(Box::new(f)),
+            panic_policy,
+        },
+    );
     handle
 }
 
@@ -45,33 +106,102 @@ pub fn unregister_task(handle: TaskHandle) {
     list.remove(&handle);
 }
 
-pub fn send_event<T, R>(event: T) -> R
+/// Retrieves every task panic recorded since the last call to [`clear_failed_tasks`]
+pub fn get_failed_tasks() -> Vec<TaskFailure> {
+    FAILED_TASKS.lock().unwrap().clone()
+}
+
+pub fn clear_failed_tasks() {
+    FAILED_TASKS.lock().unwrap().clear();
+}
+
+/// Starts a coroutine that will be resumed by the main thread once per loop.
+///
+/// The coroutine can suspend itself through its context,
+/// for example to wait for some time or for a condition to be true
+///
+/// # Arguments
+/// * `producer` - Creates the body of the coroutine from its context
+///
+/// # Returns
+/// * The handle to cancel the coroutine with
+pub fn register_coroutine<F, R>(producer: F) -> CoroutineHandle
 where
-    T: FnOnce() -> R + Send + Sync + 'static,
-    R: Send + Sync + 'static,
+    F: FnOnce(CoroutineContext) -> R,
+    R: Future<Output = ()> + Send + 'static,
 {
-    let id = EventHandle::new();
-    EVENT_LIST.lock().unwrap().insert(
-        id,
-        Box::new(|| unsafe { mem::transmute(Box::new(event())) }),
-    );
+    NEW_COROUTINES.lock().unwrap().start(producer)
+}
 
-    while !EVENT_RETURN.lock().unwrap().contains_key(&id) {
-        thread::sleep(SLEEP_DURATION);
+pub fn unregister_coroutine(handle: CoroutineHandle) {
+    if !NEW_COROUTINES.lock().unwrap().cancel(handle) {
+        CANCELLED_COROUTINES.lock().unwrap().push(handle);
     }
+}
 
-    let generic = EVENT_RETURN.lock().unwrap().remove(&id).unwrap();
+/// Checks whether the current thread is the one running the entry point loop
+pub fn is_main_thread() -> bool {
+    *MAIN_THREAD.lock().unwrap() == Some(thread::current().id())
+}
 
-    // Since the request id is unique for each request,
-    // we know the request will take the result of event()
-    // (which is of type R) and wrap it in a Box,
-    // therefore the result can't be anything but a Box<R>.
-    // Therefore, it is safe to transmute the Box without
-    // any runtime checks, and dereference its value to return it
-    unsafe {
-        let result: Box<R> = mem::transmute(generic);
-        *result
+/// Runs the given closure on the main thread and waits for its result.
+///
+/// If the closure panics, the panic is resumed on the calling thread.
+/// When called from the main thread itself, the closure is run immediately
+///
+/// # Arguments
+/// * `event` - The closure to run
+///
+/// # Returns
+/// * The value returned by the closure
+pub fn send_event<T, R>(event: T) -> R
+where
+    T: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    if is_main_thread() {
+        return event();
     }
+    send_event_async(event).wait()
+}
+
+/// Schedules the given closure to run on the main thread without waiting for it.
+///
+/// If the entry point loop isn't running, the closure is discarded
+///
+/// # Arguments
+/// * `event` - The closure to run
+///
+/// # Returns
+/// * A handle to retrieve the value returned by the closure,
+/// which can also be awaited as a future
+pub fn send_event_async<T, R>(event: T) -> EventResult<R>
+where
+    T: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let slot = Arc::new(ReplySlot::new());
+    let reply = ReplySender {
+        slot: Some(slot.clone()),
+    };
+
+    let job: MainThreadEvent = Box::new(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(event));
+        reply.send(result);
+    });
+
+    // The lock keeps the loop from shutting down between the check and the send.
+    // Jobs sent while it isn't running are dropped along with the
+    // reply sender, which marks the result as abandoned
+    let main_thread = MAIN_THREAD.lock().unwrap();
+    if main_thread.is_some() {
+        let _ = EVENT_QUEUE.sender.lock().unwrap().send(job);
+    } else {
+        drop(job);
+    }
+    drop(main_thread);
+
+    EventResult { slot }
 }
 
 pub fn register_shutdown_event<T>(f: T)
@@ -81,6 +211,134 @@ where
     SHUTDOWN_EVENT_LIST.lock().unwrap().push(Box::new(f));
 }
 
+struct ReplyState<R> {
+    result: Option<thread::Result<R>>,
+    abandoned: bool,
+    waker: Option<Waker>,
+}
+
+struct ReplySlot<R> {
+    state: Mutex<ReplyState<R>>,
+    condition: Condvar,
+}
+
+impl<R> ReplySlot<R> {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(ReplyState {
+                result: None,
+                abandoned: false,
+                waker: None,
+            }),
+            condition: Condvar::new(),
+        }
+    }
+
+    fn complete(&self, result: Option<thread::Result<R>>) {
+        let mut state = self.state.lock().unwrap();
+        match result {
+            Some(result) => state.result = Some(result),
+            None => state.abandoned = true,
+        }
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.condition.notify_all();
+    }
+}
+
+// Sending end of a reply slot. If dropped without sending,
+// the event was discarded and any waiter is woken up
+struct ReplySender<R> {
+    slot: Option<Arc<ReplySlot<R>>>,
+}
+
+impl<R> ReplySender<R> {
+    fn send(mut self, result: thread::Result<R>) {
+        if let Some(slot) = self.slot.take() {
+            slot.complete(Some(result));
+        }
+    }
+}
+
+impl<R> Drop for ReplySender<R> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot.take() {
+            slot.complete(None);
+        }
+    }
+}
+
+/// The pending result of a closure sent to the main thread
+pub struct EventResult<R> {
+    slot: Arc<ReplySlot<R>>,
+}
+
+impl<R> EventResult<R> {
+    /// Checks whether the closure has completed, or was discarded
+    pub fn is_done(&self) -> bool {
+        let state = self.slot.state.lock().unwrap();
+        state.result.is_some() || state.abandoned
+    }
+
+    /// Blocks until the closure has run on the main thread
+    ///
+    /// # Returns
+    /// * The value returned by the closure
+    ///
+    /// # Panics
+    /// * If the closure panicked, or if it was discarded because the
+    /// main thread wasn't running or shut down
+    pub fn wait(self) -> R {
+        let mut state = self.slot.state.lock().unwrap();
+        loop {
+            if let Some(result) = Self::take_result(&mut state) {
+                return result;
+            }
+            state = self.slot.condition.wait(state).unwrap();
+        }
+    }
+
+    /// Retrieves the result of the closure without blocking
+    ///
+    /// # Returns
+    /// * The value returned by the closure, or `None` if it
+    /// hasn't run yet or the value was already taken
+    ///
+    /// # Panics
+    /// * Same as [`EventResult::wait`]
+    pub fn try_take(&mut self) -> Option<R> {
+        let mut state = self.slot.state.lock().unwrap();
+        Self::take_result(&mut state)
+    }
+
+    fn take_result(state: &mut ReplyState<R>) -> Option<R> {
+        match state.result.take() {
+            Some(Ok(value)) => Some(value),
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            None if state.abandoned => {
+                panic!("The main thread wasn't running to handle the event")
+            }
+            None => None,
+        }
+    }
+}
+
+impl<R> Future for EventResult<R> {
+    type Output = R;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.slot.state.lock().unwrap();
+        match Self::take_result(&mut state) {
+            Some(value) => Poll::Ready(value),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! spaghetti_entry_point {
     ($function:ident($($arg:expr),*)) => {{
@@ -107,6 +365,8 @@ pub fn main_thread_entry_point<T>(thread_body: T)
 where
     T: Fn() + Send + 'static,
 {
+    *MAIN_THREAD.lock().unwrap() = Some(thread::current().id());
+
     let thread = thread::spawn(move || {
         thread_body();
     });
@@ -123,6 +383,8 @@ where
     T: Fn() + Send + 'static,
     F: Fn(bool) -> bool,
 {
+    *MAIN_THREAD.lock().unwrap() = Some(thread::current().id());
+
     let thread = thread::spawn(move || {
         thread_body();
     });
@@ -135,36 +397,79 @@ where
 }
 
 fn do_loop_body() {
-    thread::sleep(SLEEP_DURATION);
-
     {
-        let mut events = EVENT_LIST.lock().unwrap();
-        while events.len() > 0 {
-            let mut handle: Option<EventHandle> = None;
-            for (id, _) in events.iter() {
-                handle = Some(*id);
-                break;
+        // Wake up as soon as an event arrives, or keep the tasks running
+        let receiver = EVENT_QUEUE.receiver.lock().unwrap();
+        match receiver.recv_timeout(SLEEP_DURATION) {
+            Ok(event) => {
+                event();
+                while let Ok(event) = receiver.try_recv() {
+                    event();
+                }
             }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {}
+        }
+    }
 
-            match handle {
-                Some(id) => match events.remove(&id) {
-                    Some(event) => {
-                        let result = event();
-                        EVENT_RETURN.lock().unwrap().insert(id, result);
-                    }
-                    None => {}
-                },
-                None => {}
+    job_system::run_main_thread_jobs();
+
+    // Tasks run without holding the lock, so that they can register and unregister tasks
+    let tasks: Vec<_> = TASK_LIST
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .filter_map(|(handle, entry)| Some((*handle, entry.task.take()?, entry.panic_policy)))
+        .collect();
+    for (handle, mut task, panic_policy) in tasks {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| task()));
+        let keep = match result {
+            Ok(_) => true,
+            Err(payload) => {
+                let message = panics::payload_message(payload.as_ref());
+                let unregistered = panic_policy == PanicPolicy::Unregister;
+                log!(
+                    Error,
+                    "A main thread task panicked{}: {}",
+                    if unregistered { " and was unregistered" } else { "" },
+                    message
+                );
+
+                FAILED_TASKS.lock().unwrap().push(TaskFailure {
+                    handle,
+                    message,
+                    unregistered,
+                });
+                !unregistered
+            }
+        };
+
+        // The task may have been unregistered while running
+        let mut list = TASK_LIST.lock().unwrap();
+        match list.get_mut(&handle) {
+            Some(entry) if keep => entry.task = Some(task),
+            _ => {
+                list.remove(&handle);
             }
         }
     }
 
-    {
-        let mut tasks = TASK_LIST.lock().unwrap();
-        for (_, task) in tasks.iter_mut() {
-            task();
-        }
+    resume_coroutines();
+}
+
+fn resume_coroutines() {
+    let now = Instant::now();
+    let delta = match LAST_RESUME.lock().unwrap().replace(now) {
+        Some(last) => now.duration_since(last),
+        None => Duration::ZERO,
+    };
+
+    let mut coroutines = COROUTINES.lock().unwrap();
+    coroutines.append(&mut NEW_COROUTINES.lock().unwrap());
+    for handle in CANCELLED_COROUTINES.lock().unwrap().drain(..) {
+        coroutines.cancel(handle);
     }
+    coroutines.resume(delta);
 }
 
 fn shutdown() {
@@ -185,7 +490,20 @@ fn shutdown() {
         }
     }
 
+    // Jobs can't be discarded without leaving their waiters hanging
+    job_system::run_main_thread_jobs();
+
     TASK_LIST.lock().unwrap().clear();
-    EVENT_LIST.lock().unwrap().clear();
-    EVENT_RETURN.lock().unwrap().clear();
+    *COROUTINES.lock().unwrap() = Coroutines::new();
+    *NEW_COROUTINES.lock().unwrap() = Coroutines::new();
+    CANCELLED_COROUTINES.lock().unwrap().clear();
+    *LAST_RESUME.lock().unwrap() = None;
+
+    // Discarding the pending events wakes up whoever is waiting on them,
+    // and no more events are queued once the main thread is gone
+    *MAIN_THREAD.lock().unwrap() = None;
+    let receiver = EVENT_QUEUE.receiver.lock().unwrap();
+    while let Ok(event) = receiver.try_recv() {
+        drop(event);
+    }
 }
@@ -1,28 +1,123 @@
+use crate::core::audio_manager::AudioManager;
+use crate::core::component_runner::ComponentRunner;
+use crate::core::game_builder::GameBuilder;
+use crate::core::network_manager::NetworkManager;
+use crate::core::renderer::Renderer;
+use crate::core::updater::Updater;
 use crate::events::event_dispatcher::EventDispatcher;
+use crate::events::event_registry::{self, RegistrationError};
+use crate::log;
 use crate::settings::GameSettings;
 use crate::utils::Logger;
 use crate::world::client_state::ClientState;
-use crate::world::GameState;
+use crate::world::{GameMode, GameState};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use std::thread::ThreadId;
-use std::{sync, thread};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread::{JoinHandle, ThreadId};
+use std::{io, sync, thread};
 
 static GAMES: RwLock<Vec<sync::Weak<Game>>> = RwLock::new(Vec::new());
 static LINKS: Lazy<RwLock<HashMap<ThreadId, sync::Weak<Game>>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
+static NEXT_INDEX: AtomicU64 = AtomicU64::new(0);
+
+// Unlinks the current thread when dropped, even while unwinding
+struct LinkGuard;
+
+impl Drop for LinkGuard {
+    fn drop(&mut self) {
+        Game::unlink_current_thread();
+    }
+}
+
+/// Why a game couldn't run, or stopped abnormally
+#[derive(Debug)]
+pub enum RunError {
+    AlreadyRunning,
+    /// Event types collided when registering
+    InvalidEventRegistry(RegistrationError),
+    /// Components panicked or didn't terminate in time, by name
+    ComponentsFailed(Vec<String>),
+}
+
+impl Error for RunError {}
+
+impl Display for RunError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::AlreadyRunning => write!(f, "The game is already running"),
+            RunError::InvalidEventRegistry(error) => write!(f, "Invalid event registry: {}", error),
+            RunError::ComponentsFailed(components) => {
+                write!(f, "Components failed: {}", components.join(", "))
+            }
+        }
+    }
+}
 
 pub struct Game {
+    index: u64,
+    name: String,
     event_dispatcher: EventDispatcher,
-    game_state: GameState,
-    client_state: ClientState,
+    game_state: RwLock<GameState>,
+    client_state: RwLock<ClientState>,
     game_settings: Arc<GameSettings>,
     logger: Arc<Logger>,
     is_client: bool,
+    is_headless: bool,
+    running: AtomicBool,
+    stop_requested: Mutex<bool>,
+    stop_condition: Condvar,
 }
 
 impl Game {
+    /// Starts building a new game instance
+    ///
+    /// # Returns
+    /// * A builder with default settings, configured as a client
+    pub fn builder() -> GameBuilder {
+        GameBuilder::new()
+    }
+
+    pub(crate) fn new(
+        name: String,
+        settings: GameSettings,
+        is_client: bool,
+        is_headless: bool,
+        game_mode: Option<Box<dyn GameMode>>,
+    ) -> Arc<Self> {
+        let mut game_state = GameState::new();
+        if let Some(game_mode) = game_mode {
+            game_state.set_game_mode(game_mode);
+        }
+
+        let game = Arc::new_cyclic(|weak| Self {
+            index: NEXT_INDEX.fetch_add(1, Ordering::SeqCst),
+            name,
+            event_dispatcher: EventDispatcher::new_for_game(is_client),
+            game_state: RwLock::new(game_state),
+            client_state: RwLock::new(ClientState::new()),
+            game_settings: Arc::new(settings),
+            logger: Logger::new(weak.clone()),
+            is_client,
+            is_headless,
+            running: AtomicBool::new(false),
+            stop_requested: Mutex::new(false),
+            stop_condition: Condvar::new(),
+        });
+        game.game_settings.link_game(Arc::downgrade(&game));
+        game.get_game_state_mut()
+            .get_scheduler_mut()
+            .link_game(Arc::downgrade(&game));
+        game.logger.listen_to_settings(&game.event_dispatcher);
+
+        GAMES.write().unwrap().push(Arc::downgrade(&game));
+        game
+    }
+
     pub fn get_instance() -> sync::Weak<Game> {
         let links = LINKS.read().unwrap();
         match links.get(&thread::current().id()) {
@@ -43,6 +138,184 @@ impl Game {
         }
     }
 
+    /// Finds the game with the given index
+    ///
+    /// # Arguments
+    /// * `index` - The index of the game
+    ///
+    /// # Returns
+    /// * The game, or an empty pointer if no live game has that index
+    pub fn get_by_index(index: u64) -> sync::Weak<Game> {
+        let games = GAMES.read().unwrap();
+        for game in games.iter() {
+            if let Some(game) = game.upgrade() {
+                if game.index == index {
+                    return Arc::downgrade(&game);
+                }
+            }
+        }
+        sync::Weak::new()
+    }
+
+    /// Finds the first game with the given name
+    ///
+    /// # Arguments
+    /// * `name` - The name of the game
+    ///
+    /// # Returns
+    /// * The game, or an empty pointer if no live game has that name
+    pub fn get_by_name(name: &str) -> sync::Weak<Game> {
+        let games = GAMES.read().unwrap();
+        for game in games.iter() {
+            if let Some(game) = game.upgrade() {
+                if game.name == name {
+                    return Arc::downgrade(&game);
+                }
+            }
+        }
+        sync::Weak::new()
+    }
+
+    /// Spawns a thread linked to this game for its whole lifetime
+    ///
+    /// # Arguments
+    /// * `name` - The name of the thread
+    /// * `f` - The body of the thread
+    ///
+    /// # Returns
+    /// * The handle of the thread, or the error that prevented its creation
+    pub fn spawn_thread<F, T>(self: &Arc<Self>, name: &str, f: F) -> io::Result<JoinHandle<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        Self::spawn_linked_thread(&Arc::downgrade(self), name, f)
+    }
+
+    /// Spawns a thread linked to the given game, if it is still alive
+    ///
+    /// # Arguments
+    /// * `game` - The game
+    /// * `name` - The name of the thread
+    /// * `f` - The body of the thread
+    ///
+    /// # Returns
+    /// * The handle of the thread, or the error that prevented its creation
+    pub fn spawn_linked_thread<F, T>(
+        game: &sync::Weak<Game>,
+        name: &str,
+        f: F,
+    ) -> io::Result<JoinHandle<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let game = game.clone();
+        thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                let _guard = LinkGuard;
+                if let Some(game) = game.upgrade() {
+                    Game::link_current_thread(&game);
+                }
+                f()
+            })
+    }
+
+    /// Links the current thread to the given game, so that
+    /// [`Game::get_instance`] returns it when called from this thread
+    ///
+    /// # Arguments
+    /// * `game` - The game to link the current thread to
+    pub fn link_current_thread(game: &Arc<Game>) {
+        LINKS
+            .write()
+            .unwrap()
+            .insert(thread::current().id(), Arc::downgrade(game));
+    }
+
+    /// Removes the link between the current thread and its game, if any
+    pub fn unlink_current_thread() {
+        LINKS.write().unwrap().remove(&thread::current().id());
+    }
+
+    /// Runs the game until [`Game::stop`] is called.
+    ///
+    /// Each component of the game (updater, renderer, audio, network)
+    /// runs on its own thread linked to this game, while the calling
+    /// thread blocks until every component has shut down.
+    ///
+    /// Headless games have no renderer or audio, so they
+    /// don't need to be run through the entry point.
+    ///
+    /// If a component panics, the game stops as if [`Game::stop`] was called
+    ///
+    /// # Returns
+    /// * Nothing, or why the game couldn't run or stopped abnormally
+    pub fn run(self: &Arc<Self>) -> Result<(), RunError> {
+        // Events of colliding types would reach the wrong listeners and peers
+        if let Err(error) = event_registry::check_registrations() {
+            log!(self.logger, Fatal, &error, "Event types collided when registering");
+            return Err(RunError::InvalidEventRegistry(error));
+        }
+        if self.running.swap(true, Ordering::SeqCst) {
+            log!(self.logger, Warning, "Tried to run a game that is already running");
+            return Err(RunError::AlreadyRunning);
+        }
+        *self.stop_requested.lock().unwrap() = false;
+
+        log!(
+            self.logger,
+            Loading,
+            "Initializing {}{} game",
+            if self.is_headless { "headless " } else { "" },
+            if self.is_client { "client" } else { "server" }
+        );
+
+        let mut runner = ComponentRunner::new(Arc::downgrade(self));
+        runner.add_component("updater", Box::new(Updater::new()));
+        if self.is_client && !self.is_headless {
+            runner.add_component("renderer", Box::new(Renderer::new()));
+            runner.add_component("audio", Box::new(AudioManager::new()));
+        }
+        runner.add_component("network", Box::new(NetworkManager::new()));
+
+        if runner.start() {
+            let mut stop_requested = self.stop_requested.lock().unwrap();
+            while !*stop_requested {
+                stop_requested = self.stop_condition.wait(stop_requested).unwrap();
+            }
+        } else {
+            log!(self.logger, Fatal, "Game initialization failed");
+        }
+
+        let failed = runner.stop();
+        log!(self.logger, Loading, "Game terminated");
+
+        self.running.store(false, Ordering::SeqCst);
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            let error = RunError::ComponentsFailed(failed);
+            log!(self.logger, Error, &error, "The game stopped abnormally");
+            Err(error)
+        }
+    }
+
+    /// Asks the game to shut down
+    pub fn stop(&self) {
+        *self.stop_requested.lock().unwrap() = true;
+        self.stop_condition.notify_all();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn is_stop_requested(&self) -> bool {
+        *self.stop_requested.lock().unwrap()
+    }
+
     pub fn get_event_dispatcher(&self) -> &EventDispatcher {
         &self.event_dispatcher
     }
@@ -51,8 +324,24 @@ impl Game {
         self.is_client
     }
 
-    pub fn get_game_state(&self) -> &GameState {
-        &self.game_state
+    pub fn is_headless(&self) -> bool {
+        self.is_headless
+    }
+
+    pub fn get_game_state(&self) -> RwLockReadGuard<'_, GameState> {
+        self.game_state.read().unwrap()
+    }
+
+    pub fn get_game_state_mut(&self) -> RwLockWriteGuard<'_, GameState> {
+        self.game_state.write().unwrap()
+    }
+
+    pub fn get_client_state(&self) -> RwLockReadGuard<'_, ClientState> {
+        self.client_state.read().unwrap()
+    }
+
+    pub fn get_client_state_mut(&self) -> RwLockWriteGuard<'_, ClientState> {
+        self.client_state.write().unwrap()
     }
 
     pub fn get_settings(&self) -> &GameSettings {
@@ -63,7 +352,26 @@ impl Game {
         self.logger.clone()
     }
 
+    /// The index of this game, unique within the process
     pub fn get_index(&self) -> u64 {
-        0
+        self.index
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Drop for Game {
+    fn drop(&mut self) {
+        // Our own weak pointers can't be upgraded anymore at this point
+        GAMES
+            .write()
+            .unwrap()
+            .retain(|game| game.strong_count() > 0);
+        LINKS
+            .write()
+            .unwrap()
+            .retain(|_, game| game.strong_count() > 0);
     }
 }
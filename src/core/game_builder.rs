@@ -0,0 +1,111 @@
+use crate::core::Game;
+use crate::log;
+use crate::settings::GameSettings;
+use crate::world::GameMode;
+use std::sync::{Arc, Once};
+
+static SIGNAL_HANDLER: Once = Once::new();
+
+/// Collects everything needed to create a [`Game`] instance
+///
+/// A game created through [`GameBuilder::build`] is registered
+/// in the list of live instances and linked to the calling thread
+pub struct GameBuilder {
+    name: String,
+    settings: Option<GameSettings>,
+    is_client: bool,
+    is_headless: bool,
+    stop_on_interrupt: bool,
+    game_mode: Option<Box<dyn GameMode>>,
+}
+
+impl GameBuilder {
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            settings: None,
+            is_client: true,
+            is_headless: false,
+            stop_on_interrupt: false,
+            game_mode: None,
+        }
+    }
+
+    /// Tags the game with a name, to tell it apart from other instances
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Uses the given settings instead of the default ones
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// The game will act as a client
+    pub fn as_client(mut self) -> Self {
+        self.is_client = true;
+        self
+    }
+
+    /// The game will act as a server
+    pub fn as_server(mut self) -> Self {
+        self.is_client = false;
+        self
+    }
+
+    /// The game will act as a dedicated server,
+    /// with no window, rendering or audio
+    pub fn headless(mut self) -> Self {
+        self.is_client = false;
+        self.is_headless = true;
+        self
+    }
+
+    /// Every game will be stopped when the process receives
+    /// an interrupt or termination signal
+    pub fn stop_on_interrupt(mut self) -> Self {
+        self.stop_on_interrupt = true;
+        self
+    }
+
+    /// Uses the given game mode instead of the empty one
+    pub fn with_game_mode(mut self, game_mode: Box<dyn GameMode>) -> Self {
+        self.game_mode = Some(game_mode);
+        self
+    }
+
+    /// Creates the game, registers it and links it to the current thread
+    ///
+    /// # Returns
+    /// * The newly created game
+    pub fn build(self) -> Arc<Game> {
+        let settings = self.settings.unwrap_or_else(GameSettings::new);
+        let game = Game::new(
+            self.name,
+            settings,
+            self.is_client,
+            self.is_headless,
+            self.game_mode,
+        );
+        Game::link_current_thread(&game);
+
+        if self.stop_on_interrupt {
+            install_signal_handler();
+        }
+        game
+    }
+}
+
+fn install_signal_handler() {
+    SIGNAL_HANDLER.call_once(|| {
+        let result = ctrlc::set_handler(|| {
+            log!(Info, "Shutdown signal received");
+            Game::with_all_instances(|game| game.stop());
+        });
+        if let Err(error) = result {
+            log!(Error, &error, "Couldn't install the shutdown signal handler");
+        }
+    });
+}
@@ -0,0 +1,600 @@
+use crate::core::{entry_point, Game};
+use crate::log;
+use crate::utils::panics;
+use once_cell::sync::Lazy;
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use std::{mem, sync, thread};
+
+static GLOBAL: Lazy<JobSystem> = Lazy::new(|| {
+    let threads = thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(2);
+    JobSystem::new(threads.saturating_sub(1).max(1))
+});
+
+// Jobs that must run on the main thread, drained by the entry point loop
+static MAIN_THREAD_JOBS: Mutex<VecDeque<Job>> = Mutex::new(VecDeque::new());
+
+static IDLE_TIMEOUT: Duration = Duration::from_millis(10);
+static NEXT_POOL_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // The pool id and queue index of the worker running on this thread
+    static CURRENT_WORKER: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Affinity {
+    Any,
+    MainThread,
+}
+
+struct JobStateInner {
+    done: bool,
+    panicked: bool,
+    dependents: Vec<Arc<PendingJob>>,
+}
+
+struct JobState {
+    inner: Mutex<JobStateInner>,
+    condition: Condvar,
+}
+
+impl JobState {
+    fn new() -> Self {
+        Self {
+            inner: Mutex::new(JobStateInner {
+                done: false,
+                panicked: false,
+                dependents: Vec::new(),
+            }),
+            condition: Condvar::new(),
+        }
+    }
+
+    fn complete(&self, panicked: bool) {
+        let dependents = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.done = true;
+            inner.panicked = panicked;
+            mem::take(&mut inner.dependents)
+        };
+        self.condition.notify_all();
+
+        for dependent in dependents {
+            dependent.dependency_done();
+        }
+    }
+}
+
+struct Job {
+    run: Box<dyn FnOnce() + Send>,
+    state: Arc<JobState>,
+    game: sync::Weak<Game>,
+}
+
+impl Job {
+    fn execute(self) {
+        // The thread may be helping out while linked to another game
+        let previous = Game::get_instance();
+        let game = self.game.upgrade();
+        if let Some(game) = &game {
+            Game::link_current_thread(game);
+        }
+        let result = panic::catch_unwind(AssertUnwindSafe(self.run));
+        if game.is_some() {
+            match previous.upgrade() {
+                Some(previous) => Game::link_current_thread(&previous),
+                None => Game::unlink_current_thread(),
+            }
+        }
+
+        let panicked = match result {
+            Ok(_) => false,
+            Err(payload) => {
+                log!(
+                    Error,
+                    "A job panicked: {}",
+                    panics::payload_message(payload.as_ref())
+                );
+                true
+            }
+        };
+
+        self.state.complete(panicked);
+    }
+
+    /// Completes the job without running it, as if it had panicked.
+    /// Used for the jobs left over when a pool shuts down
+    fn cancel(self) {
+        self.state.complete(true);
+    }
+}
+
+// A job waiting for its dependencies to complete
+struct PendingJob {
+    remaining: AtomicUsize,
+    job: Mutex<Option<Job>>,
+    shared: Arc<Shared>,
+    affinity: Affinity,
+}
+
+impl PendingJob {
+    fn dependency_done(&self) {
+        if self.remaining.fetch_sub(1, Ordering::SeqCst) == 1 {
+            if let Some(job) = self.job.lock().unwrap().take() {
+                self.shared.push(job, self.affinity);
+            }
+        }
+    }
+}
+
+/// Allows to check on or wait for the completion of a job
+#[derive(Clone)]
+pub struct JobHandle {
+    state: Arc<JobState>,
+    shared: Arc<Shared>,
+}
+
+impl JobHandle {
+    pub fn is_done(&self) -> bool {
+        self.state.inner.lock().unwrap().done
+    }
+
+    /// Checks whether the job has completed by panicking,
+    /// or was cancelled because its pool was dropped
+    pub fn has_panicked(&self) -> bool {
+        self.state.inner.lock().unwrap().panicked
+    }
+
+    /// Blocks until the job has completed.
+    ///
+    /// While waiting, the current thread helps running other jobs
+    pub fn wait(&self) {
+        loop {
+            if self.is_done() {
+                return;
+            }
+            // Main thread jobs can't run anywhere else
+            let job = if entry_point::is_main_thread() {
+                pop_main_thread_job()
+            } else {
+                None
+            };
+            if let Some(job) = job.or_else(|| self.shared.find_job()) {
+                job.execute();
+                continue;
+            }
+
+            let inner = self.state.inner.lock().unwrap();
+            if !inner.done {
+                let _ = self.state.condition.wait_timeout(inner, IDLE_TIMEOUT);
+            }
+        }
+    }
+}
+
+struct Shared {
+    id: usize,
+    queues: Vec<Mutex<VecDeque<Job>>>,
+    injector: Mutex<VecDeque<Job>>,
+    generation: Mutex<u64>,
+    work_available: Condvar,
+    shutdown: AtomicBool,
+}
+
+impl Shared {
+    fn push(&self, job: Job, affinity: Affinity) {
+        if affinity == Affinity::MainThread {
+            MAIN_THREAD_JOBS.lock().unwrap().push_back(job);
+            return;
+        }
+
+        // Workers push to their own queue, everyone else to the injector
+        let mut queue = match self.current_worker() {
+            Some(index) => self.queues[index].lock().unwrap(),
+            None => self.injector.lock().unwrap(),
+        };
+        // Checked under the queue lock, so the job can't
+        // be pushed after the queues are drained
+        if self.shutdown.load(Ordering::SeqCst) {
+            drop(queue);
+            job.cancel();
+            return;
+        }
+        queue.push_back(job);
+        drop(queue);
+
+        *self.generation.lock().unwrap() += 1;
+        self.work_available.notify_one();
+    }
+
+    fn current_worker(&self) -> Option<usize> {
+        match CURRENT_WORKER.with(|worker| worker.get()) {
+            Some((pool, index)) if pool == self.id => Some(index),
+            _ => None,
+        }
+    }
+
+    // Cancels every job still queued, so that nobody waits for them forever
+    fn drain(&self) {
+        let mut jobs: Vec<Job> = self.injector.lock().unwrap().drain(..).collect();
+        for queue in self.queues.iter() {
+            jobs.extend(queue.lock().unwrap().drain(..));
+        }
+        for job in jobs {
+            job.cancel();
+        }
+    }
+
+    fn find_job(&self) -> Option<Job> {
+        let own = self.current_worker();
+
+        // Newest job from our own queue first, it's the most likely to be cache-hot
+        if let Some(index) = own {
+            if let Some(job) = self.queues[index].lock().unwrap().pop_back() {
+                return Some(job);
+            }
+        }
+
+        if let Some(job) = self.injector.lock().unwrap().pop_front() {
+            return Some(job);
+        }
+
+        // Steal the oldest job from someone else
+        let start = own.unwrap_or(0);
+        for offset in 1..=self.queues.len() {
+            let index = (start + offset) % self.queues.len();
+            if Some(index) == own {
+                continue;
+            }
+            if let Some(job) = self.queues[index].lock().unwrap().pop_front() {
+                return Some(job);
+            }
+        }
+        None
+    }
+}
+
+/// A pool of worker threads running jobs, with work stealing.
+///
+/// Most code should use the pool returned by [`JobSystem::global`]
+pub struct JobSystem {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl JobSystem {
+    /// Creates a new pool
+    ///
+    /// # Arguments
+    /// * `workers` - The amount of worker threads, at least one
+    pub fn new(workers: usize) -> Self {
+        let workers = workers.max(1);
+        let shared = Arc::new(Shared {
+            id: NEXT_POOL_ID.fetch_add(1, Ordering::SeqCst),
+            queues: (0..workers).map(|_| Mutex::new(VecDeque::new())).collect(),
+            injector: Mutex::new(VecDeque::new()),
+            generation: Mutex::new(0),
+            work_available: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+        });
+
+        let mut handles = Vec::new();
+        for index in 0..workers {
+            let shared = shared.clone();
+            let handle = thread::Builder::new()
+                .name(format!("job_worker_{}", index))
+                .spawn(move || worker_thread(shared, index));
+            match handle {
+                Ok(handle) => handles.push(handle),
+                Err(error) => log!(Error, &error, "Couldn't spawn job worker {}", index),
+            }
+        }
+
+        Self {
+            shared,
+            workers: handles,
+        }
+    }
+
+    /// The pool shared by the whole engine, with one
+    /// worker for each available core except one
+    pub fn global() -> &'static JobSystem {
+        &GLOBAL
+    }
+
+    pub fn get_worker_count(&self) -> usize {
+        self.shared.queues.len()
+    }
+
+    /// Runs a job on any worker.
+    ///
+    /// Jobs spawned by a thread linked to a game are run linked to the same game
+    ///
+    /// # Arguments
+    /// * `f` - The job
+    ///
+    /// # Returns
+    /// * The handle of the job
+    pub fn spawn<F>(&self, f: F) -> JobHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.spawn_after(&[], f)
+    }
+
+    /// Runs a job on any worker, once all its dependencies have completed
+    ///
+    /// # Arguments
+    /// * `dependencies` - The jobs that must complete first
+    /// * `f` - The job
+    ///
+    /// # Returns
+    /// * The handle of the job
+    pub fn spawn_after<F>(&self, dependencies: &[JobHandle], f: F) -> JobHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.schedule(dependencies, Box::new(f), Affinity::Any)
+    }
+
+    /// Runs a job on the main thread, during the entry point loop
+    ///
+    /// # Arguments
+    /// * `f` - The job
+    ///
+    /// # Returns
+    /// * The handle of the job
+    pub fn spawn_on_main<F>(&self, f: F) -> JobHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.spawn_on_main_after(&[], f)
+    }
+
+    /// Runs a job on the main thread, once all its dependencies have completed
+    ///
+    /// # Arguments
+    /// * `dependencies` - The jobs that must complete first
+    /// * `f` - The job
+    ///
+    /// # Returns
+    /// * The handle of the job
+    pub fn spawn_on_main_after<F>(&self, dependencies: &[JobHandle], f: F) -> JobHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.schedule(dependencies, Box::new(f), Affinity::MainThread)
+    }
+
+    fn schedule(
+        &self,
+        dependencies: &[JobHandle],
+        run: Box<dyn FnOnce() + Send>,
+        affinity: Affinity,
+    ) -> JobHandle {
+        let state = Arc::new(JobState::new());
+        let job = Job {
+            run,
+            state: state.clone(),
+            game: Game::get_instance(),
+        };
+
+        // One extra dependency is held until registration is over,
+        // so that the job isn't pushed while we're still registering
+        let pending = Arc::new(PendingJob {
+            remaining: AtomicUsize::new(dependencies.len() + 1),
+            job: Mutex::new(Some(job)),
+            shared: self.shared.clone(),
+            affinity,
+        });
+
+        for dependency in dependencies.iter() {
+            let mut inner = dependency.state.inner.lock().unwrap();
+            if inner.done {
+                drop(inner);
+                pending.dependency_done();
+            } else {
+                inner.dependents.push(pending.clone());
+            }
+        }
+        pending.dependency_done();
+
+        JobHandle {
+            state,
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Runs jobs that can borrow from the current stack frame.
+    ///
+    /// Returns only once every job spawned in the scope has completed.
+    /// If any of them panicked, the panic is resumed on the calling thread
+    ///
+    /// # Arguments
+    /// * `f` - Spawns the jobs through the given scope
+    ///
+    /// # Returns
+    /// * The value returned by `f`
+    pub fn scope<'scope, F, R>(&'scope self, f: F) -> R
+    where
+        F: FnOnce(&Scope<'scope>) -> R,
+    {
+        let scope = Scope {
+            system: self,
+            handles: Mutex::new(Vec::new()),
+            panic: Arc::new(Mutex::new(None)),
+            _marker: PhantomData,
+        };
+
+        // The guard waits for the jobs even if `f` panics, so they
+        // can never outlive the data they borrow
+        let guard = ScopeGuard { scope: &scope };
+        let result = f(&scope);
+        drop(guard);
+
+        if let Some(payload) = scope.panic.lock().unwrap().take() {
+            panic::resume_unwind(payload);
+        }
+        result
+    }
+
+    /// Calls `f` on every element of the slice, in parallel
+    pub fn parallel_for<T, F>(&self, items: &[T], f: F)
+    where
+        T: Sync,
+        F: Fn(&T) + Sync,
+    {
+        let chunk_size = self.chunk_size(items.len());
+        if chunk_size >= items.len() {
+            // Not worth a job
+            items.iter().for_each(f);
+            return;
+        }
+        let f = &f;
+        self.scope(|scope| {
+            for chunk in items.chunks(chunk_size) {
+                scope.spawn(move || chunk.iter().for_each(f));
+            }
+        });
+    }
+
+    /// Calls `f` on every element of the slice, in parallel
+    pub fn parallel_for_mut<T, F>(&self, items: &mut [T], f: F)
+    where
+        T: Send,
+        F: Fn(&mut T) + Sync,
+    {
+        let chunk_size = self.chunk_size(items.len());
+        if chunk_size >= items.len() {
+            // Not worth a job
+            items.iter_mut().for_each(f);
+            return;
+        }
+        let f = &f;
+        self.scope(|scope| {
+            for chunk in items.chunks_mut(chunk_size) {
+                scope.spawn(move || chunk.iter_mut().for_each(f));
+            }
+        });
+    }
+
+    fn chunk_size(&self, len: usize) -> usize {
+        // A few chunks per worker, so that stealing can balance the load
+        let chunks = self.get_worker_count() * 4;
+        len.div_ceil(chunks).max(1)
+    }
+}
+
+impl Drop for JobSystem {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        self.shared.drain();
+        self.shared.work_available.notify_all();
+
+        // The pool may be dropped by one of its own jobs, that worker exits
+        // once the job returns and can't be joined from itself
+        let current = thread::current().id();
+        for worker in self.workers.drain(..) {
+            if worker.thread().id() != current {
+                let _ = worker.join();
+            }
+        }
+    }
+}
+
+/// Spawns jobs that may borrow anything that outlives the scope
+pub struct Scope<'scope> {
+    system: &'scope JobSystem,
+    handles: Mutex<Vec<JobHandle>>,
+    panic: Arc<Mutex<Option<Box<dyn Any + Send>>>>,
+    _marker: PhantomData<fn(&'scope ()) -> &'scope ()>,
+}
+
+impl<'scope> Scope<'scope> {
+    /// Runs a job on any worker
+    ///
+    /// # Arguments
+    /// * `f` - The job
+    ///
+    /// # Returns
+    /// * The handle of the job
+    pub fn spawn<F>(&self, f: F) -> JobHandle
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        let panic_slot = self.panic.clone();
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(f)) {
+                let mut slot = panic_slot.lock().unwrap();
+                if slot.is_none() {
+                    *slot = Some(payload);
+                }
+            }
+        });
+
+        // The scope doesn't return before every job has completed,
+        // so the job can't outlive anything it borrows
+        let job: Box<dyn FnOnce() + Send + 'static> = unsafe { mem::transmute(job) };
+        let handle = self.system.schedule(&[], job, Affinity::Any);
+        self.handles.lock().unwrap().push(handle.clone());
+        handle
+    }
+}
+
+struct ScopeGuard<'a, 'scope> {
+    scope: &'a Scope<'scope>,
+}
+
+impl<'a, 'scope> Drop for ScopeGuard<'a, 'scope> {
+    fn drop(&mut self) {
+        // Jobs may spawn more jobs while we wait
+        loop {
+            let handles = mem::take(&mut *self.scope.handles.lock().unwrap());
+            if handles.is_empty() {
+                break;
+            }
+            for handle in handles.iter() {
+                handle.wait();
+            }
+        }
+    }
+}
+
+/// Runs every job queued for the main thread. Called by the entry point loop
+pub(crate) fn run_main_thread_jobs() {
+    while let Some(job) = pop_main_thread_job() {
+        job.execute();
+    }
+}
+
+fn pop_main_thread_job() -> Option<Job> {
+    MAIN_THREAD_JOBS.lock().unwrap().pop_front()
+}
+
+fn worker_thread(shared: Arc<Shared>, index: usize) {
+    CURRENT_WORKER.with(|worker| worker.set(Some((shared.id, index))));
+
+    while !shared.shutdown.load(Ordering::SeqCst) {
+        let generation = *shared.generation.lock().unwrap();
+        if let Some(job) = shared.find_job() {
+            job.execute();
+            continue;
+        }
+
+        // Sleep until new work is pushed, unless it was pushed while we were looking
+        let current = shared.generation.lock().unwrap();
+        if *current == generation && !shared.shutdown.load(Ordering::SeqCst) {
+            let _ = shared.work_available.wait_timeout(current, IDLE_TIMEOUT);
+        }
+    }
+}
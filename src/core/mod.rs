@@ -1,6 +1,21 @@
+pub mod audio_manager;
+pub mod component_runner;
+pub mod coroutine;
 pub mod entry_point;
 pub mod game;
+pub mod game_builder;
+pub mod job_system;
+pub mod network_manager;
+pub mod renderer;
 pub mod thread_component;
+pub mod updater;
 
+pub use component_runner::ComponentRunner;
+pub use coroutine::CoroutineContext;
+pub use coroutine::CoroutineHandle;
 pub use game::Game;
+pub use game::RunError;
+pub use game_builder::GameBuilder;
+pub use job_system::JobHandle;
+pub use job_system::JobSystem;
 pub use thread_component::ThreadComponent;
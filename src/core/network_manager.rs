@@ -0,0 +1,42 @@
+use crate::core::{Game, ThreadComponent};
+use crate::log;
+use std::thread;
+use std::time::Duration;
+
+static SLEEP_DURATION: Duration = Duration::from_millis(1);
+
+/// Exchanges data between client and server
+pub struct NetworkManager {}
+
+impl NetworkManager {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ThreadComponent for NetworkManager {
+    fn initialize(&mut self) {
+        if let Some(game) = Game::get_instance().upgrade() {
+            if !game.is_client() {
+                let settings = game.get_settings();
+                log!(
+                    Info,
+                    "Server configured on port {} for up to {} clients",
+                    settings.get("online.port").as_unsigned_int_or(9018),
+                    settings.get("online.maxClients").as_unsigned_int_or(10)
+                );
+            }
+        }
+    }
+
+    fn post_initialize(&mut self) {}
+
+    fn loop_cycle(&mut self, _delta: f32) {
+        // TODO NETWORKING
+        thread::sleep(SLEEP_DURATION);
+    }
+
+    fn pre_terminate(&mut self) {}
+
+    fn terminate(&mut self) {}
+}
@@ -0,0 +1,29 @@
+use crate::core::ThreadComponent;
+use std::thread;
+use std::time::Duration;
+
+static SLEEP_DURATION: Duration = Duration::from_millis(1);
+
+/// Draws the game world on the client
+pub struct Renderer {}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ThreadComponent for Renderer {
+    fn initialize(&mut self) {}
+
+    fn post_initialize(&mut self) {}
+
+    fn loop_cycle(&mut self, _delta: f32) {
+        // TODO RENDERING
+        thread::sleep(SLEEP_DURATION);
+    }
+
+    fn pre_terminate(&mut self) {}
+
+    fn terminate(&mut self) {}
+}
@@ -1,4 +1,4 @@
-pub trait ThreadComponent {
+pub trait ThreadComponent: Send {
     fn initialize(&mut self);
     fn post_initialize(&mut self);
     fn loop_cycle(&mut self, delta: f32);
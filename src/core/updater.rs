@@ -0,0 +1,74 @@
+use crate::core::{Game, ThreadComponent};
+use std::time::Duration;
+use std::{sync, thread};
+
+static SLEEP_DURATION: Duration = Duration::from_millis(1);
+
+/// Processes game events and updates the game state
+pub struct Updater {
+    game: sync::Weak<Game>,
+    looping: bool,
+}
+
+impl Updater {
+    pub fn new() -> Self {
+        Self {
+            game: sync::Weak::new(),
+            looping: false,
+        }
+    }
+}
+
+impl ThreadComponent for Updater {
+    fn initialize(&mut self) {
+        self.game = Game::get_instance();
+
+        if let Some(game) = self.game.upgrade() {
+            let settings = game.get_settings();
+            let mut game_state = game.get_game_state_mut();
+            let clock = game_state.get_clock_mut();
+            clock.set_tick_rate(settings.get("engine.tickRate").as_unsigned_int_or(60));
+            clock.set_max_catch_up(
+                settings
+                    .get("engine.maxCatchUpTicks")
+                    .as_unsigned_int_or(5) as u32,
+            );
+        }
+    }
+
+    fn post_initialize(&mut self) {}
+
+    fn loop_cycle(&mut self, delta: f32) {
+        if let Some(game) = self.game.upgrade() {
+            // The game may be running again after being stopped. Events raised while
+            // components initialize are discarded, the first tick only comes afterwards
+            if !self.looping {
+                game.get_event_dispatcher().restart();
+                self.looping = true;
+            }
+            game.get_event_dispatcher().process_events();
+            game.get_game_state_mut().advance(delta);
+        }
+        thread::sleep(SLEEP_DURATION);
+    }
+
+    fn pre_terminate(&mut self) {}
+
+    fn terminate(&mut self) {
+        if let Some(game) = self.game.upgrade() {
+            game.get_event_dispatcher().shutdown();
+            game.get_game_state_mut().destroy();
+        }
+    }
+}
+
+impl Drop for Updater {
+    fn drop(&mut self) {
+        // Nothing processes events anymore, threads waiting for them must not block forever
+        if thread::panicking() {
+            if let Some(game) = self.game.upgrade() {
+                game.get_event_dispatcher().shutdown();
+            }
+        }
+    }
+}
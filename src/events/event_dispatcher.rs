@@ -1,17 +1,76 @@
-use crate::events::event_listener::EventListener;
+use crate::events::event_interceptor::{EventInterceptor, InterceptAction, InterceptContext};
+use crate::events::event_listener::{BoundEL, EventListener, TypedEL, TypedEventListener};
+use crate::events::event_recording::{EventRecorder, EventReplayer};
 use crate::events::event_registry::EventType;
+use crate::events::event_route::{EventRoute, EventTarget, EventTransport, RoutingError};
+use crate::events::listener_options::ListenerOptions;
 use crate::events::EventSource::{Client, Server};
 use crate::events::{event_registry, GameEvent};
+use crate::log;
+use crate::networking::network_buffer::NetworkBuffer;
+use crate::networking::token::Token;
 use crate::utils::id_type::id_type;
+use crate::utils::panics;
 use crate::utils::types::*;
+use crate::utils::PanicPolicy;
 use std::collections::{HashMap, VecDeque};
 use std::hash::Hasher;
-use std::sync::Mutex;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+use std::{mem, thread};
+
+/// Describes a listener that panicked while handling an event
+#[derive(Clone)]
+pub struct ListenerFailure {
+    pub handle: ListenerHandle,
+    pub event_type: EventType,
+    pub message: String,
+    pub unregistered: bool,
+}
+
+/// How long to wait before raising a scheduled event
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum EventDelay {
+    /// A number of calls to [`EventDispatcher::process_events`]
+    Ticks(u64),
+    /// An amount of time, rounded up to the next call to [`EventDispatcher::process_events`]
+    Time(Duration),
+}
+
+impl From<u64> for EventDelay {
+    fn from(ticks: u64) -> Self {
+        EventDelay::Ticks(ticks)
+    }
+}
+
+impl From<Duration> for EventDelay {
+    fn from(duration: Duration) -> Self {
+        EventDelay::Time(duration)
+    }
+}
 
 pub struct EventDispatcher {
     is_client: bool,
     events: MutexVecDeque<EventRequest>,
-    listeners: MutexHashMap<EventType, Vec<ListenerEntry>>,
+    // Shared with the guards of the listeners
+    listeners: Arc<ListenerMap>,
+    failed_listeners: MutexVec<ListenerFailure>,
+    processor: Mutex<Option<ThreadId>>,
+    dispatching: AtomicBool,
+    tick: AtomicU64,
+    recorder: Mutex<Option<EventRecorder>>,
+    replayer: Mutex<Option<EventReplayer>>,
+    transport: Mutex<Option<Arc<dyn EventTransport>>>,
+    interceptors: MutexVec<(InterceptorHandle, Arc<dyn EventInterceptor>)>,
+    // Delayed events, along with the tick they are due at
+    delayed: MutexVec<(u64, EventRequest)>,
+    scheduled: MutexVec<ScheduledEvent>,
+    shut_down: AtomicBool,
+    // Owned by a game, whose updater processes the events
+    game_owned: bool,
 }
 
 impl EventDispatcher {
@@ -19,62 +78,624 @@ impl EventDispatcher {
         Self {
             is_client,
             events: Mutex::new(VecDeque::new()),
-            listeners: Mutex::new(HashMap::new()),
+            listeners: Arc::new(Mutex::new(HashMap::new())),
+            failed_listeners: Mutex::new(Vec::new()),
+            processor: Mutex::new(None),
+            dispatching: AtomicBool::new(false),
+            tick: AtomicU64::new(0),
+            recorder: Mutex::new(None),
+            replayer: Mutex::new(None),
+            transport: Mutex::new(None),
+            interceptors: Mutex::new(Vec::new()),
+            delayed: Mutex::new(Vec::new()),
+            scheduled: Mutex::new(Vec::new()),
+            shut_down: AtomicBool::new(false),
+            game_owned: false,
+        }
+    }
+
+    /// Creates the dispatcher of a game. Events raised from threads other than the one
+    /// processing them are always queued, and the dispatcher stays shut down until
+    /// it is restarted by the game
+    pub(crate) fn new_for_game(is_client: bool) -> Self {
+        Self {
+            shut_down: AtomicBool::new(true),
+            game_owned: true,
+            ..Self::new(is_client)
+        }
+    }
+
+    /// Queues an event to be dispatched by the next call to [`EventDispatcher::process_events`]
+    ///
+    /// # Arguments
+    /// * `event` - The event
+    /// * `is_async` - If `false`, blocks until the event has been handled
+    pub fn raise_event(&self, event: Box<dyn GameEvent>, is_async: bool) {
+        if is_async {
+            self.queue_event(event, None);
+        } else {
+            self.raise_event_and_wait(event);
+        }
+    }
+
+    /// Queues an event and blocks until it has been handled.
+    ///
+    /// If called from the thread that processes the events of this dispatcher,
+    /// for example by a listener, or if no thread has processed the events of a dispatcher
+    /// not owned by a game yet, the event is dispatched immediately instead,
+    /// and can't be delayed by interceptors.
+    /// Once the dispatcher is shut down, the event is returned cancelled without being dispatched
+    ///
+    /// # Arguments
+    /// * `event` - The event
+    ///
+    /// # Returns
+    /// * The event after every listener has handled it
+    pub fn raise_event_and_wait(&self, event: Box<dyn GameEvent>) -> Box<dyn GameEvent> {
+        let inline = match *self.processor.lock().unwrap() {
+            Some(processor) => processor == thread::current().id(),
+            // The updater of the game will process it
+            None => !self.game_owned,
+        };
+        if inline && !self.is_shut_down() {
+            let derived = self.is_dispatching();
+            let mut request = EventRequest::new(self.set_origin(event), None, derived);
+            self.intercept_event(&mut request, false);
+            self.record_event(&request);
+            self.dispatch_event(&mut request);
+            self.route_event(&request);
+            return request.event;
+        }
+
+        let completion = Arc::new(EventCompletion::new());
+        self.queue_event(event, Some(completion.clone()));
+        completion.wait()
+    }
+
+    /// Same as [`EventDispatcher::raise_event_and_wait`], for events of a known type
+    ///
+    /// # Arguments
+    /// * `event` - The event
+    ///
+    /// # Returns
+    /// * The event after every listener has handled it
+    pub fn raise_typed_event_and_wait<T: GameEvent>(&self, event: Box<T>) -> Box<T> {
+        match self.raise_event_and_wait(event).downcast::<T>() {
+            Ok(event) => event,
+            Err(_) => panic!("A listener replaced the event with one of another type"),
+        }
+    }
+
+    /// Raises an event after a delay, without blocking
+    ///
+    /// # Arguments
+    /// * `event` - The event
+    /// * `delay` - An amount of ticks, or a [`Duration`]
+    ///
+    /// # Returns
+    /// * The handle of the scheduled event, to cancel it
+    pub fn raise_after<D: Into<EventDelay>>(
+        &self,
+        event: Box<dyn GameEvent>,
+        delay: D,
+    ) -> ScheduledEventHandle {
+        let due = match delay.into() {
+            EventDelay::Ticks(ticks) => ScheduledTime::Tick(self.get_tick() + ticks),
+            EventDelay::Time(duration) => ScheduledTime::Instant(Instant::now() + duration),
+        };
+        self.schedule_event(event, due)
+    }
+
+    /// Raises an event during the given tick, without blocking.
+    /// If the tick has already passed, the event is raised during the next one
+    ///
+    /// # Arguments
+    /// * `event` - The event
+    /// * `tick` - The tick, as returned by [`EventDispatcher::get_tick`]
+    ///
+    /// # Returns
+    /// * The handle of the scheduled event, to cancel it
+    pub fn raise_at(&self, event: Box<dyn GameEvent>, tick: u64) -> ScheduledEventHandle {
+        self.schedule_event(event, ScheduledTime::Tick(tick))
+    }
+
+    fn schedule_event(
+        &self,
+        event: Box<dyn GameEvent>,
+        due: ScheduledTime,
+    ) -> ScheduledEventHandle {
+        let handle = ScheduledEventHandle::new();
+        let derived = self.is_dispatching();
+        let request = EventRequest::new(self.set_origin(event), None, derived);
+
+        // Checked under the lock, so that the event can't be pushed after shutdown clears them
+        let mut scheduled = self.scheduled.lock().unwrap();
+        if self.is_shut_down() {
+            // Nothing will ever raise it
+            return handle;
+        }
+        scheduled.push(ScheduledEvent {
+            handle,
+            due,
+            request,
+        });
+        handle
+    }
+
+    /// Cancels a scheduled event
+    ///
+    /// # Returns
+    /// * Whether the event was still scheduled
+    pub fn cancel_scheduled(&self, handle: ScheduledEventHandle) -> bool {
+        let mut scheduled = self.scheduled.lock().unwrap();
+        let len = scheduled.len();
+        scheduled.retain(|x| x.handle != handle);
+        scheduled.len() != len
+    }
+
+    pub fn is_scheduled(&self, handle: ScheduledEventHandle) -> bool {
+        self.scheduled
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|x| x.handle == handle)
+    }
+
+    pub fn get_scheduled_count(&self) -> usize {
+        self.scheduled.lock().unwrap().len()
+    }
+
+    /// Discards every scheduled and delayed event. Threads waiting for
+    /// a queued or delayed event get it back cancelled, without it being dispatched.
+    /// Events queued or scheduled afterwards are discarded immediately
+    pub fn shutdown(&self) {
+        // The flag is set under both locks, so that no event is queued or scheduled
+        // between checking it and pushing the event
+        let queued = {
+            let mut scheduled = self.scheduled.lock().unwrap();
+            let mut events = self.events.lock().unwrap();
+            self.shut_down.store(true, Ordering::SeqCst);
+            scheduled.clear();
+            mem::take(&mut *events)
+        };
+        *self.processor.lock().unwrap() = None;
+
+        let delayed = mem::take(&mut *self.delayed.lock().unwrap());
+        for request in delayed.into_iter().map(|x| x.1).chain(queued) {
+            request.discard();
+        }
+    }
+
+    /// Lets the dispatcher raise events again after [`EventDispatcher::shutdown`],
+    /// for example when a stopped game runs again
+    pub fn restart(&self) {
+        self.shut_down.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        self.shut_down.load(Ordering::SeqCst)
+    }
+
+    fn queue_event(&self, event: Box<dyn GameEvent>, completion: Option<Arc<EventCompletion>>) {
+        let derived = self.is_dispatching();
+        let request = EventRequest::new(self.set_origin(event), completion, derived);
+
+        // Checked under the lock, so that the event can't be pushed after shutdown drains them
+        let mut events = self.events.lock().unwrap();
+        if self.is_shut_down() {
+            // Nothing will ever dispatch it
+            drop(events);
+            request.discard();
+            return;
         }
+        events.push_back(request);
     }
 
-    pub fn raise_event(&self, mut event: Box<dyn GameEvent>, is_async: bool) {
-        // Set the origin of the event
+    fn set_origin(&self, mut event: Box<dyn GameEvent>) -> Box<dyn GameEvent> {
         event
             .get_event_data_mut()
             .set_from(if self.is_client { Client } else { Server });
+        event
+    }
 
-        // Construct request
-        let request = EventRequest::new(event);
-        let request_id = request.request_id.clone();
+    // Whether the current thread is dispatching an event of this dispatcher
+    fn is_dispatching(&self) -> bool {
+        self.dispatching.load(Ordering::SeqCst)
+            && *self.processor.lock().unwrap() == Some(thread::current().id())
+    }
 
-        // Send the request
-        self.events.lock().unwrap().push_back(request);
+    fn record_event(&self, request: &EventRequest) {
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            recorder.record(self.get_tick(), request.derived, request.event.as_ref());
+        }
+    }
 
-        // If not async wait for completion
-        while !is_async {
-            if self
-                .events
-                .lock()
-                .unwrap()
-                .iter()
-                .position(|x| x.request_id == request_id)
-                .is_none()
+    fn dispatch_event(&self, request: &mut EventRequest) {
+        let event_type = request.event.get_event_type();
+
+        // Listeners are called without holding the lock,
+        // so that they can raise events and register listeners
+        let list = match self.listeners.lock().unwrap().get(&event_type) {
+            Some(list) => list.clone(),
+            None => return,
+        };
+
+        // A panicking listener must not prevent the others from receiving the event
+        for listener_entry in list.iter() {
+            // Once cancelled, the event only reaches listeners that asked for it
+            if request.event.get_event_data().is_cancelled()
+                && !listener_entry.options.receive_cancelled
             {
-                break;
+                continue;
+            }
+
+            // Its owner is gone
+            if listener_entry.listener.is_expired() {
+                self.unregister_event_listener(listener_entry.entry_id);
+                continue;
+            }
+
+            // Unregistered before being called, so that events it raises don't reach it
+            if listener_entry.options.once {
+                if listener_entry.fired.swap(true, Ordering::SeqCst) {
+                    continue;
+                }
+                self.unregister_event_listener(listener_entry.entry_id);
+            }
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                listener_entry.listener.handle_event(&mut request.event)
+            }));
+
+            if let Err(payload) = result {
+                let message = panics::payload_message(payload.as_ref());
+                let unregistered = listener_entry.options.panic_policy == PanicPolicy::Unregister;
+                log!(
+                    Error,
+                    "An event listener panicked{}: {}",
+                    if unregistered {
+                        " and was unregistered"
+                    } else {
+                        ""
+                    },
+                    message
+                );
+
+                if unregistered {
+                    self.unregister_event_listener(listener_entry.entry_id);
+                }
+                self.failed_listeners.lock().unwrap().push(ListenerFailure {
+                    handle: listener_entry.entry_id,
+                    event_type,
+                    message,
+                    unregistered,
+                });
             }
         }
     }
 
-    fn dispatch_event(&self, request: &mut EventRequest) {
-        let listener_map = self.listeners.lock().unwrap();
+    /// Dispatches every event queued so far, then wakes up whoever is waiting for them.
+    ///
+    /// Events raised while processing are dispatched by the next call
+    pub fn process_events(&self) {
+        *self.processor.lock().unwrap() = Some(thread::current().id());
+        self.prune_expired_listeners();
 
-        // Are there any listeners for this event type?
-        if let Some(list) = listener_map.get(&request.event.get_event_type()) {
-            // Iterate over them and trigger them
-            for listener_entry in list.iter() {
-                listener_entry.listener.handle_event(&mut request.event);
+        // Replayed events go first, they were raised before anything raised this tick
+        let mut requests = VecDeque::new();
+        if let Some(replayer) = self.replayer.lock().unwrap().as_mut() {
+            for event in replayer.take_due(self.get_tick()) {
+                // Replayed events already travelled and were validated when they were recorded
+                let mut request = EventRequest::new(event, None, false);
+                request.replayed = true;
+                requests.push_back(request);
             }
         }
+
+        // Then delayed events, which were also raised before
+        let tick = self.get_tick();
+        let delayed = mem::take(&mut *self.delayed.lock().unwrap());
+        let (due, waiting): (Vec<_>, Vec<_>) = delayed.into_iter().partition(|x| x.0 <= tick);
+        self.delayed.lock().unwrap().extend(waiting);
+        requests.extend(due.into_iter().map(|x| x.1));
+
+        // Then scheduled events, in order of scheduling
+        let now = Instant::now();
+        let scheduled = mem::take(&mut *self.scheduled.lock().unwrap());
+        let (due, waiting): (Vec<_>, Vec<_>) = scheduled.into_iter().partition(|x| match x.due {
+            ScheduledTime::Tick(due) => due <= tick,
+            ScheduledTime::Instant(due) => due <= now,
+        });
+        self.scheduled.lock().unwrap().extend(waiting);
+        requests.extend(due.into_iter().map(|x| x.request));
+
+        requests.append(&mut *self.events.lock().unwrap());
+
+        self.dispatching.store(true, Ordering::SeqCst);
+        for mut request in requests {
+            if let Some(delay) = self.intercept_event(&mut request, true) {
+                self.delayed
+                    .lock()
+                    .unwrap()
+                    .push((tick + delay.max(1), request));
+                continue;
+            }
+
+            self.record_event(&request);
+            self.dispatch_event(&mut request);
+            self.route_event(&request);
+            if let Some(completion) = request.completion {
+                completion.complete(request.event);
+            }
+        }
+        self.dispatching.store(false, Ordering::SeqCst);
+
+        self.tick.fetch_add(1, Ordering::SeqCst);
     }
 
-    pub fn process_events(&self) {
-        let mut list = self.events.lock().unwrap();
-        for event in list.iter_mut() {
-            self.dispatch_event(event);
+    // Passes the event through every interceptor. If the event can't be delayed,
+    // interceptors asking for it are treated as if they let it continue
+    //
+    // Returns the amount of ticks to delay the event by, if an interceptor asked for it
+    fn intercept_event(&self, request: &mut EventRequest, can_delay: bool) -> Option<u64> {
+        // Interceptors are called without holding the lock, like listeners
+        let interceptors = self.interceptors.lock().unwrap().clone();
+        let context = InterceptContext {
+            tick: self.get_tick(),
+            remote: request.remote,
+        };
+
+        for (_, interceptor) in interceptors.iter() {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                interceptor.intercept(&mut request.event, &context)
+            }));
+
+            match result {
+                Ok(InterceptAction::Continue) => {}
+                Ok(InterceptAction::Cancel) => {
+                    request.event.get_event_data_mut().set_cancelled(true);
+                }
+                Ok(InterceptAction::Delay(ticks)) if can_delay => return Some(ticks),
+                Ok(InterceptAction::Delay(_)) => {}
+                Err(payload) => {
+                    log!(
+                        Error,
+                        "An event interceptor panicked: {}",
+                        panics::payload_message(payload.as_ref())
+                    );
+                }
+            }
         }
+        None
+    }
+
+    /// Registers an interceptor, called after every interceptor registered
+    /// before it and before any listener, for every event
+    ///
+    /// # Arguments
+    /// * `interceptor` - The interceptor
+    ///
+    /// # Returns
+    /// * The handle of the interceptor
+    pub fn add_interceptor(&self, interceptor: Box<dyn EventInterceptor>) -> InterceptorHandle {
+        let handle = InterceptorHandle::new();
+        self.interceptors
+            .lock()
+            .unwrap()
+            .push((handle, Arc::from(interceptor)));
+        handle
+    }
+
+    pub fn remove_interceptor(&self, handle: InterceptorHandle) {
+        self.interceptors.lock().unwrap().retain(|x| x.0 != handle);
+    }
+
+    /// The amount of events delayed by interceptors, still waiting to be dispatched
+    pub fn get_delayed_count(&self) -> usize {
+        self.delayed.lock().unwrap().len()
     }
 
+    // Sends a locally raised event to the peers its route leads to
+    fn route_event(&self, request: &EventRequest) {
+        if request.remote || request.replayed || request.event.get_event_data().is_cancelled() {
+            return;
+        }
+
+        let event_type = request.event.get_event_type();
+        let target = match event_registry::get_event_route(event_type) {
+            EventRoute::LocalOnly => return,
+            EventRoute::ToServer if self.is_client => EventTarget::Server,
+            EventRoute::ToAllClients if !self.is_client => EventTarget::AllClients,
+            EventRoute::ToOwner if !self.is_client => {
+                match request.event.get_event_data().get_owner() {
+                    Some(owner) => EventTarget::Client(owner),
+                    None => {
+                        log!(
+                            Warning,
+                            "Event of type {:?} is routed to its owner, but has none",
+                            event_type
+                        );
+                        return;
+                    }
+                }
+            }
+            _ => return,
+        };
+
+        let transport = match self.transport.lock().unwrap().as_ref() {
+            Some(transport) => transport.clone(),
+            None => return,
+        };
+        let mut buffer = NetworkBuffer::new();
+        event_registry::serialize_event(request.event.as_ref(), &mut buffer);
+        transport.send(target, buffer);
+    }
+
+    /// Sets what delivers events to other peers. Until one is set,
+    /// events are only dispatched locally regardless of their route
+    pub fn set_transport(&self, transport: Option<Arc<dyn EventTransport>>) {
+        *self.transport.lock().unwrap() = transport;
+    }
+
+    /// Queues an event sent by another peer, to be dispatched by the next call to
+    /// [`EventDispatcher::process_events`]. Received events are never sent again.
+    ///
+    /// The server only accepts events from clients that are routed to it, and clients only
+    /// accept events from the server that are routed to them. The server also marks each
+    /// event as owned by the client that sent it, so that clients can't impersonate each other.
+    /// Received events are never cancelled, even if the sender cancelled them
+    ///
+    /// # Arguments
+    /// * `buffer` - The buffer the event was serialized into
+    /// * `sender` - The client that sent the event. Ignored by clients
+    ///
+    /// # Returns
+    /// * Nothing, or the reason the event was rejected
+    pub fn receive_remote_event(
+        &self,
+        buffer: &mut NetworkBuffer,
+        sender: Option<Token>,
+    ) -> Result<(), RoutingError> {
+        let mut event = event_registry::deserialize_event(buffer)?;
+        let event_type = event.get_event_type();
+        let route = event_registry::get_event_route(event_type);
+
+        let data = event.get_event_data_mut();
+        if self.is_client {
+            if data.get_from() != Server {
+                return Err(RoutingError::SpoofedSource(event_type));
+            }
+            if !route.is_sent_by_server() {
+                return Err(RoutingError::WrongDirection(event_type));
+            }
+        } else {
+            if data.get_from() != Client {
+                return Err(RoutingError::SpoofedSource(event_type));
+            }
+            if !route.is_sent_by_client() {
+                return Err(RoutingError::WrongDirection(event_type));
+            }
+            match sender {
+                Some(sender) => data.set_owner(Some(sender)),
+                None => return Err(RoutingError::UnknownSender),
+            }
+        }
+
+        // Only the peer the event came from is trusted, not what it wrote
+        data.set_from(if self.is_client { Server } else { Client });
+        data.set_cancelled(false);
+
+        let mut request = EventRequest::new(event, None, false);
+        request.remote = true;
+        self.events.lock().unwrap().push_back(request);
+        Ok(())
+    }
+
+    /// The amount of times [`EventDispatcher::process_events`] has been called
+    pub fn get_tick(&self) -> u64 {
+        self.tick.load(Ordering::SeqCst)
+    }
+
+    /// Records every event dispatched from now on, until
+    /// [`EventDispatcher::stop_recording`] is called.
+    /// Replaces the recording in progress, if any
+    ///
+    /// # Arguments
+    /// * `recorder` - Where to record the events
+    pub fn start_recording(&self, mut recorder: EventRecorder) {
+        recorder.set_start_tick(self.get_tick());
+        *self.recorder.lock().unwrap() = Some(recorder);
+    }
+
+    /// Stops recording events
+    ///
+    /// # Returns
+    /// * The recorder, to be finished by the caller, or `None` if no recording was in progress
+    pub fn stop_recording(&self) -> Option<EventRecorder> {
+        self.recorder.lock().unwrap().take()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.lock().unwrap().is_some()
+    }
+
+    /// Raises the events of a recording, each one by the call to
+    /// [`EventDispatcher::process_events`] matching the tick it was recorded at.
+    /// The first tick of the recording is the next call.
+    /// Replaces the replay in progress, if any
+    ///
+    /// # Arguments
+    /// * `replayer` - The recording
+    pub fn start_replay(&self, mut replayer: EventReplayer) {
+        replayer.set_start_tick(self.get_tick());
+        *self.replayer.lock().unwrap() = Some(replayer);
+    }
+
+    pub fn stop_replay(&self) {
+        *self.replayer.lock().unwrap() = None;
+    }
+
+    /// Whether a replay is in progress and has events left
+    pub fn is_replaying(&self) -> bool {
+        match self.replayer.lock().unwrap().as_ref() {
+            Some(replayer) => !replayer.is_finished(),
+            None => false,
+        }
+    }
+
+    pub fn get_pending_count(&self) -> usize {
+        self.events.lock().unwrap().len()
+    }
+
+    /// Registers a listener for events of type `T`, with default options.
+    ///
+    /// If the listener panics, it is unregistered
     pub fn register_event_listener<T: GameEvent + 'static>(
         &self,
         listener: Box<dyn EventListener>,
     ) -> Option<ListenerHandle> {
-        let entry = ListenerEntry::new(listener);
+        self.register_event_listener_with_options::<T>(listener, ListenerOptions::new())
+    }
+
+    /// Registers a listener for events of type `T`
+    ///
+    /// # Arguments
+    /// * `listener` - The listener
+    /// * `panic_policy` - What to do with the listener if it panics
+    ///
+    /// # Returns
+    /// * The handle of the listener, or `None` if `T` is not a registered event type
+    pub fn register_event_listener_with_policy<T: GameEvent + 'static>(
+        &self,
+        listener: Box<dyn EventListener>,
+        panic_policy: PanicPolicy,
+    ) -> Option<ListenerHandle> {
+        self.register_event_listener_with_options::<T>(
+            listener,
+            ListenerOptions::new().with_panic_policy(panic_policy),
+        )
+    }
+
+    /// Registers a listener for events of type `T`.
+    ///
+    /// Listeners are called in order of priority, and in order
+    /// of registration among listeners of the same priority
+    ///
+    /// # Arguments
+    /// * `listener` - The listener
+    /// * `options` - The priority of the listener, whether it receives
+    /// cancelled events, whether it is called only once and what to do with it if it panics
+    ///
+    /// # Returns
+    /// * The handle of the listener, or `None` if `T` is not a registered event type
+    pub fn register_event_listener_with_options<T: GameEvent + 'static>(
+        &self,
+        listener: Box<dyn EventListener>,
+        options: ListenerOptions,
+    ) -> Option<ListenerHandle> {
+        self.prune_expired_listeners();
+
+        let entry = ListenerEntry::new(listener, options);
         let entry_id = entry.entry_id.clone();
         let event_type = event_registry::get_event_type_of::<T>();
 
@@ -87,40 +708,234 @@ impl EventDispatcher {
 
         // Lock the map and attempt to retrieve listener list
         let mut map = self.listeners.lock().unwrap();
-        let mut list = map.get_mut(&event_type);
+        let list = map.entry(event_type).or_insert_with(Vec::new);
+
+        // Add listener after every listener of the same or lower priority
+        let position = list.partition_point(|x| x.options.priority <= options.priority);
+        list.insert(position, Arc::new(entry));
 
-        // First time registering a listener of this kind
-        if list.is_none() {
-            map.insert(event_type, Vec::new());
-            list = map.get_mut(&event_type);
+        Some(entry_id)
+    }
+
+    /// Registers a closure receiving events of type `T`, with default options
+    ///
+    /// # Arguments
+    /// * `f` - The closure
+    ///
+    /// # Returns
+    /// * The handle of the listener, or `None` if `T` is not a registered event type
+    pub fn listen<T, F>(&self, f: F) -> Option<ListenerHandle>
+    where
+        T: GameEvent + 'static,
+        F: Fn(&mut T) + Send + Sync + 'static,
+    {
+        self.listen_with_options::<T, F>(f, ListenerOptions::new())
+    }
+
+    /// Registers a closure receiving events of type `T`
+    ///
+    /// # Arguments
+    /// * `f` - The closure
+    /// * `options` - The priority of the listener, whether it receives
+    /// cancelled events, whether it is called only once and what to do with it if it panics
+    ///
+    /// # Returns
+    /// * The handle of the listener, or `None` if `T` is not a registered event type
+    pub fn listen_with_options<T, F>(
+        &self,
+        f: F,
+        options: ListenerOptions,
+    ) -> Option<ListenerHandle>
+    where
+        T: GameEvent + 'static,
+        F: Fn(&mut T) + Send + Sync + 'static,
+    {
+        self.register_typed_listener::<T, F>(f, options)
+    }
+
+    /// Registers a listener for events of type `T`, which receives them already downcast
+    ///
+    /// # Arguments
+    /// * `listener` - The listener
+    /// * `options` - The priority of the listener, whether it receives
+    /// cancelled events, whether it is called only once and what to do with it if it panics
+    ///
+    /// # Returns
+    /// * The handle of the listener, or `None` if `T` is not a registered event type
+    pub fn register_typed_listener<T, L>(
+        &self,
+        listener: L,
+        options: ListenerOptions,
+    ) -> Option<ListenerHandle>
+    where
+        T: GameEvent + 'static,
+        L: TypedEventListener<T> + 'static,
+    {
+        self.register_event_listener_with_options::<T>(
+            Box::new(TypedEL::<T, L>::new(listener)),
+            options,
+        )
+    }
+
+    /// Registers a closure receiving the first event of type `T` dispatched from now on
+    ///
+    /// # Arguments
+    /// * `f` - The closure
+    ///
+    /// # Returns
+    /// * The handle of the listener, or `None` if `T` is not a registered event type
+    pub fn listen_once<T, F>(&self, f: F) -> Option<ListenerHandle>
+    where
+        T: GameEvent + 'static,
+        F: Fn(&mut T) + Send + Sync + 'static,
+    {
+        self.listen_with_options::<T, F>(f, ListenerOptions::new().once())
+    }
+
+    /// Registers a closure receiving events of type `T` for as long as `owner` is alive.
+    /// Once the owner is dropped, the listener is unregistered by the next event
+    /// of type `T`, call to [`EventDispatcher::process_events`] or listener registration
+    ///
+    /// # Arguments
+    /// * `owner` - The owner of the listener, only referenced weakly
+    /// * `f` - The closure, receiving the owner along with the event
+    ///
+    /// # Returns
+    /// * The handle of the listener, or `None` if `T` is not a registered event type
+    pub fn listen_bound<O, T, F>(&self, owner: &Arc<O>, f: F) -> Option<ListenerHandle>
+    where
+        O: Send + Sync + 'static,
+        T: GameEvent + 'static,
+        F: Fn(&O, &mut T) + Send + Sync + 'static,
+    {
+        self.register_typed_listener::<T, BoundEL<O, T, F>>(
+            BoundEL::new(Arc::downgrade(owner), f),
+            ListenerOptions::new(),
+        )
+    }
+
+    /// Registers a closure receiving events of type `T` until the returned guard is dropped
+    ///
+    /// # Arguments
+    /// * `f` - The closure
+    ///
+    /// # Returns
+    /// * The guard of the listener, or `None` if `T` is not a registered event type
+    pub fn listen_guarded<T, F>(&self, f: F) -> Option<ListenerGuard>
+    where
+        T: GameEvent + 'static,
+        F: Fn(&mut T) + Send + Sync + 'static,
+    {
+        self.listen::<T, F>(f).map(|handle| self.guard(handle))
+    }
+
+    /// Wraps the handle of a listener in a guard, which unregisters the listener when dropped
+    pub fn guard(&self, handle: ListenerHandle) -> ListenerGuard {
+        ListenerGuard {
+            handle,
+            listeners: Arc::downgrade(&self.listeners),
         }
-        let list = list.unwrap();
+    }
 
-        // Add listener to list
-        list.push(entry);
+    // Unregisters the listeners whose owner is gone, whatever the type of their events
+    fn prune_expired_listeners(&self) {
+        let mut expired = Vec::new();
+        {
+            let mut map = self.listeners.lock().unwrap();
+            for list in map.values_mut() {
+                list.retain(|x| {
+                    if x.listener.is_expired() {
+                        expired.push(x.clone());
+                        return false;
+                    }
+                    true
+                });
+            }
+        }
 
-        Some(entry_id)
+        // Dropped without holding the lock, listeners can own guards
+        drop(expired);
     }
 
     pub fn unregister_event_listener(&self, id: ListenerHandle) {
-        let mut map = self.listeners.lock().unwrap();
-        for entry in map.iter_mut() {
-            entry.1.retain(|x| x.entry_id != id);
+        remove_listener(&self.listeners, id);
+    }
+
+    pub fn is_listener_registered(&self, id: ListenerHandle) -> bool {
+        self.listeners
+            .lock()
+            .unwrap()
+            .values()
+            .any(|list| list.iter().any(|x| x.entry_id == id))
+    }
+
+    /// Retrieves every listener panic recorded since the last call
+    /// to [`EventDispatcher::clear_failed_listeners`]
+    pub fn get_failed_listeners(&self) -> Vec<ListenerFailure> {
+        self.failed_listeners.lock().unwrap().clone()
+    }
+
+    pub fn clear_failed_listeners(&self) {
+        self.failed_listeners.lock().unwrap().clear();
+    }
+}
+
+/// Unregisters a listener when dropped.
+///
+/// Dropping the guard after the dispatcher does nothing
+pub struct ListenerGuard {
+    handle: ListenerHandle,
+    listeners: Weak<ListenerMap>,
+}
+
+impl ListenerGuard {
+    pub fn get_handle(&self) -> ListenerHandle {
+        self.handle
+    }
+
+    /// Drops the guard without unregistering the listener
+    ///
+    /// # Returns
+    /// * The handle of the listener
+    pub fn forget(mut self) -> ListenerHandle {
+        self.listeners = Weak::new();
+        self.handle
+    }
+}
+
+impl Drop for ListenerGuard {
+    fn drop(&mut self) {
+        if let Some(listeners) = self.listeners.upgrade() {
+            remove_listener(&listeners, self.handle);
         }
     }
 }
 
+type ListenerMap = MutexHashMap<EventType, Vec<Arc<ListenerEntry>>>;
+
+fn remove_listener(listeners: &ListenerMap, id: ListenerHandle) {
+    let mut map = listeners.lock().unwrap();
+    for entry in map.iter_mut() {
+        entry.1.retain(|x| x.entry_id != id);
+    }
+}
+
 // ListenerEntry
 struct ListenerEntry {
     listener: Box<dyn EventListener>,
     entry_id: ListenerHandle,
+    options: ListenerOptions,
+    // Whether a once listener has been called
+    fired: AtomicBool,
 }
 
 impl ListenerEntry {
-    fn new(listener: Box<dyn EventListener>) -> Self {
+    fn new(listener: Box<dyn EventListener>, options: ListenerOptions) -> Self {
         Self {
             listener,
             entry_id: ListenerHandle::new(),
+            options,
+            fired: AtomicBool::new(false),
         }
     }
 }
@@ -128,18 +943,83 @@ impl ListenerEntry {
 // EventRequest
 struct EventRequest {
     event: Box<dyn GameEvent>,
-    request_id: EventRequestHandle,
+    completion: Option<Arc<EventCompletion>>,
+    // Raised by a listener while dispatching another event
+    derived: bool,
+    // Received from another peer
+    remote: bool,
+    // Read from a recording
+    replayed: bool,
 }
 
 impl EventRequest {
-    fn new(event: Box<dyn GameEvent>) -> Self {
+    fn new(
+        event: Box<dyn GameEvent>,
+        completion: Option<Arc<EventCompletion>>,
+        derived: bool,
+    ) -> Self {
         Self {
             event,
-            request_id: EventRequestHandle::new(),
+            completion,
+            derived,
+            remote: false,
+            replayed: false,
+        }
+    }
+
+    // Hands the event back cancelled, without dispatching it
+    fn discard(mut self) {
+        self.event.get_event_data_mut().set_cancelled(true);
+        if let Some(completion) = self.completion {
+            completion.complete(self.event);
+        }
+    }
+}
+
+// ScheduledEvent
+enum ScheduledTime {
+    Tick(u64),
+    Instant(Instant),
+}
+
+struct ScheduledEvent {
+    handle: ScheduledEventHandle,
+    due: ScheduledTime,
+    request: EventRequest,
+}
+
+// Hands a handled event back to the thread that raised it
+struct EventCompletion {
+    event: Mutex<Option<Box<dyn GameEvent>>>,
+    condition: Condvar,
+}
+
+impl EventCompletion {
+    fn new() -> Self {
+        Self {
+            event: Mutex::new(None),
+            condition: Condvar::new(),
+        }
+    }
+
+    fn complete(&self, event: Box<dyn GameEvent>) {
+        *self.event.lock().unwrap() = Some(event);
+        self.condition.notify_all();
+    }
+
+    fn wait(&self) -> Box<dyn GameEvent> {
+        let mut event = self.event.lock().unwrap();
+        loop {
+            if let Some(event) = event.take() {
+                return event;
+            }
+            event = self.condition.wait(event).unwrap();
         }
     }
 }
 
 // Handle types
 id_type!(ListenerHandle);
+id_type!(InterceptorHandle);
+id_type!(ScheduledEventHandle);
 id_type!(EventRequestHandle);
@@ -0,0 +1,194 @@
+use crate::events::event_registry::{self, EventType};
+use crate::events::{EventSource, GameEvent};
+use crate::log;
+use crate::networking::token::Token;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// What happens to an event after an interceptor has seen it
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum InterceptAction {
+    /// Passes the event to the next interceptor, then to the listeners
+    Continue,
+    /// Cancels the event. Listeners receiving cancelled events still get it
+    Cancel,
+    /// Dispatches the event again after the given amount of ticks,
+    /// going through every interceptor once more
+    Delay(u64),
+}
+
+/// Where an intercepted event comes from
+pub struct InterceptContext {
+    /// The tick of the dispatcher
+    pub tick: u64,
+    /// Whether the event was received from another peer
+    pub remote: bool,
+}
+
+/// Sees every event before any listener does, regardless of its type.
+///
+/// Interceptors are called in order of registration, and can rewrite
+/// the event, or replace it altogether
+pub trait EventInterceptor: Send + Sync {
+    fn intercept(
+        &self,
+        event: &mut Box<dyn GameEvent>,
+        context: &InterceptContext,
+    ) -> InterceptAction;
+}
+
+impl<F> EventInterceptor for F
+where
+    F: Fn(&mut Box<dyn GameEvent>, &InterceptContext) -> InterceptAction + Send + Sync,
+{
+    fn intercept(
+        &self,
+        event: &mut Box<dyn GameEvent>,
+        context: &InterceptContext,
+    ) -> InterceptAction {
+        self(event, context)
+    }
+}
+
+/// Logs every event passing through the dispatcher
+pub struct DebugTracer {}
+
+impl DebugTracer {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl EventInterceptor for DebugTracer {
+    fn intercept(
+        &self,
+        event: &mut Box<dyn GameEvent>,
+        context: &InterceptContext,
+    ) -> InterceptAction {
+        let event_type = event.get_event_type();
+        let name = event_registry::get_event_name(event_type).unwrap_or_default();
+        let data = event.get_event_data();
+        log!(
+            Debug,
+            "[tick {}] {} event {} from {}{}",
+            context.tick,
+            if context.remote { "Remote" } else { "Local" },
+            name,
+            match data.get_from() {
+                EventSource::NotSet => "nowhere",
+                EventSource::Client => "client",
+                EventSource::Server => "server",
+            },
+            if data.is_cancelled() {
+                " (cancelled)"
+            } else {
+                ""
+            }
+        );
+        InterceptAction::Continue
+    }
+}
+
+/// Limits the amount of events received from other peers in a single tick,
+/// counting the events of each owner separately.
+/// Local events are never limited
+pub struct RateLimiter {
+    limit: u32,
+    drop_excess: bool,
+    // The tick being counted, and the events received in it by each owner
+    counts: Mutex<(u64, HashMap<Option<Token>, u32>)>,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter delaying the excess events to the next tick
+    ///
+    /// # Arguments
+    /// * `limit` - The amount of events each owner can send in a single tick
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            drop_excess: false,
+            counts: Mutex::new((0, HashMap::new())),
+        }
+    }
+
+    /// Cancels the excess events instead of delaying them
+    pub fn dropping_excess(mut self) -> Self {
+        self.drop_excess = true;
+        self
+    }
+}
+
+impl EventInterceptor for RateLimiter {
+    fn intercept(
+        &self,
+        event: &mut Box<dyn GameEvent>,
+        context: &InterceptContext,
+    ) -> InterceptAction {
+        if !context.remote {
+            return InterceptAction::Continue;
+        }
+
+        let mut counts = self.counts.lock().unwrap();
+        if counts.0 != context.tick {
+            *counts = (context.tick, HashMap::new());
+        }
+        let count = counts
+            .1
+            .entry(event.get_event_data().get_owner())
+            .or_insert(0);
+        if *count < self.limit {
+            *count += 1;
+            InterceptAction::Continue
+        } else if self.drop_excess {
+            InterceptAction::Cancel
+        } else {
+            InterceptAction::Delay(1)
+        }
+    }
+}
+
+/// Remembers the type of every event it sees. Clones share what they remember,
+/// so that a clone can be registered while the original is inspected
+#[derive(Clone)]
+pub struct EventSpy {
+    events: Arc<Mutex<Vec<EventType>>>,
+}
+
+impl EventSpy {
+    pub fn new() -> Self {
+        Self {
+            events: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Retrieves the type of every event seen so far, in order
+    pub fn get_events(&self) -> Vec<EventType> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Counts the events of type `T` seen so far
+    pub fn count_of<T: GameEvent>(&self) -> usize {
+        match event_registry::get_event_type_of::<T>() {
+            Some(event_type) => self
+                .events
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|x| **x == event_type)
+                .count(),
+            None => 0,
+        }
+    }
+
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+}
+
+impl EventInterceptor for EventSpy {
+    fn intercept(&self, event: &mut Box<dyn GameEvent>, _: &InterceptContext) -> InterceptAction {
+        self.events.lock().unwrap().push(event.get_event_type());
+        InterceptAction::Continue
+    }
+}
@@ -1,21 +1,117 @@
 use crate::events::GameEvent;
+use std::marker::PhantomData;
+use std::sync::Weak;
 
-pub trait EventListener: Send {
+pub trait EventListener: Send + Sync {
     fn handle_event(&self, event: &mut Box<dyn GameEvent>);
+
+    /// Whether the listener has nothing left to listen for.
+    /// Expired listeners are unregistered instead of receiving the next event
+    fn is_expired(&self) -> bool {
+        false
+    }
 }
 
-pub struct LambdaEL<T: Fn(&mut Box<dyn GameEvent>) + Send + 'static> {
+pub struct LambdaEL<T: Fn(&mut Box<dyn GameEvent>) + Send + Sync + 'static> {
     f: T,
 }
 
-impl<T: Fn(&mut Box<dyn GameEvent>) + Send + 'static> LambdaEL<T> {
+impl<T: Fn(&mut Box<dyn GameEvent>) + Send + Sync + 'static> LambdaEL<T> {
     pub fn new(f: T) -> Self {
         Self { f }
     }
 }
 
-impl<T: Fn(&mut Box<dyn GameEvent>) + Send + 'static> EventListener for LambdaEL<T> {
+impl<T: Fn(&mut Box<dyn GameEvent>) + Send + Sync + 'static> EventListener for LambdaEL<T> {
     fn handle_event(&self, event: &mut Box<dyn GameEvent>) {
         (self.f)(event);
     }
 }
+
+/// A listener for events of a single, known type
+pub trait TypedEventListener<T: GameEvent>: Send + Sync {
+    fn handle_event(&self, event: &mut T);
+
+    /// Same as [`EventListener::is_expired`]
+    fn is_expired(&self) -> bool {
+        false
+    }
+}
+
+impl<T: GameEvent, F: Fn(&mut T) + Send + Sync> TypedEventListener<T> for F {
+    fn handle_event(&self, event: &mut T) {
+        self(event);
+    }
+}
+
+/// Adapts a [`TypedEventListener`] to an [`EventListener`],
+/// ignoring events of any other type
+pub struct TypedEL<T: GameEvent, L: TypedEventListener<T>> {
+    listener: L,
+    _marker: PhantomData<fn(&mut T)>,
+}
+
+impl<T: GameEvent, L: TypedEventListener<T>> TypedEL<T, L> {
+    pub fn new(listener: L) -> Self {
+        Self {
+            listener,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: GameEvent, L: TypedEventListener<T>> EventListener for TypedEL<T, L> {
+    fn handle_event(&self, event: &mut Box<dyn GameEvent>) {
+        if let Some(event) = event.downcast_mut::<T>() {
+            self.listener.handle_event(event);
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.listener.is_expired()
+    }
+}
+
+/// A listener for events of type `T`, which lives as long as its owner
+pub struct BoundEL<O, T, F>
+where
+    O: Send + Sync,
+    T: GameEvent,
+    F: Fn(&O, &mut T) + Send + Sync,
+{
+    owner: Weak<O>,
+    f: F,
+    _marker: PhantomData<fn(&mut T)>,
+}
+
+impl<O, T, F> BoundEL<O, T, F>
+where
+    O: Send + Sync,
+    T: GameEvent,
+    F: Fn(&O, &mut T) + Send + Sync,
+{
+    pub fn new(owner: Weak<O>, f: F) -> Self {
+        Self {
+            owner,
+            f,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<O, T, F> TypedEventListener<T> for BoundEL<O, T, F>
+where
+    O: Send + Sync,
+    T: GameEvent,
+    F: Fn(&O, &mut T) + Send + Sync,
+{
+    fn handle_event(&self, event: &mut T) {
+        if let Some(owner) = self.owner.upgrade() {
+            (self.f)(&owner, event);
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.owner.strong_count() == 0
+    }
+}
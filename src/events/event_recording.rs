@@ -0,0 +1,244 @@
+use crate::events::{event_registry, GameEvent};
+use crate::log;
+use crate::networking::network_buffer::{BufferError, NetworkBuffer};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+static MAGIC: &[u8; 8] = b"SPGEVREC";
+static VERSION: u16 = 1;
+
+/// Why a recording couldn't be replayed
+#[derive(Debug)]
+pub enum ReplayError {
+    IOError(io::Error),
+    InvalidHeader,
+    UnsupportedVersion(u16),
+    FingerprintMismatch { recorded: u64, current: u64 },
+    BufferError(BufferError),
+}
+
+impl Error for ReplayError {}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::IOError(error) => write!(f, "IOError: {}", error),
+            ReplayError::InvalidHeader => write!(f, "Not an event recording"),
+            ReplayError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported recording version {}", version)
+            }
+            ReplayError::FingerprintMismatch { recorded, current } => write!(
+                f,
+                "Recorded with event registry {:#018x}, current one is {:#018x}",
+                recorded, current
+            ),
+            ReplayError::BufferError(error) => write!(f, "BufferError: {}", error),
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::IOError(error)
+    }
+}
+
+impl From<BufferError> for ReplayError {
+    fn from(error: BufferError) -> Self {
+        ReplayError::BufferError(error)
+    }
+}
+
+/// Writes every event passing through a dispatcher, along with
+/// the tick it was dispatched at, relative to the start of the recording.
+///
+/// The recording starts with the fingerprint of the event registry,
+/// so that it is only replayed by builds that agree on event type ids
+pub struct EventRecorder {
+    writer: Box<dyn Write + Send>,
+    start_tick: u64,
+    failed: bool,
+}
+
+impl EventRecorder {
+    /// Creates a recorder writing to the given file, overwriting it
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(Box::new(BufWriter::new(File::create(path)?)))
+    }
+
+    /// Creates a recorder writing to the given writer
+    pub fn new(mut writer: Box<dyn Write + Send>) -> io::Result<Self> {
+        let mut header = NetworkBuffer::new();
+        header.write_bytes(MAGIC);
+        header.write_u16(VERSION);
+        header.write_u64(event_registry::registry_fingerprint());
+        writer.write_all(header.as_bytes())?;
+
+        Ok(Self {
+            writer,
+            start_tick: 0,
+            failed: false,
+        })
+    }
+
+    pub(crate) fn set_start_tick(&mut self, tick: u64) {
+        self.start_tick = tick;
+    }
+
+    /// Writes an event to the recording
+    ///
+    /// # Arguments
+    /// * `tick` - The tick of the dispatcher the event is dispatched at
+    /// * `derived` - Whether the event was raised by a listener while dispatching another one
+    /// * `event` - The event
+    pub(crate) fn record(&mut self, tick: u64, derived: bool, event: &dyn GameEvent) {
+        if self.failed {
+            return;
+        }
+
+        let mut record = NetworkBuffer::new();
+        record.write_u64(tick.saturating_sub(self.start_tick));
+        record.write_bool(derived);
+        event_registry::serialize_event(event, &mut record);
+
+        if let Err(error) = self.writer.write_all(record.as_bytes()) {
+            log!(Error, &error, "Couldn't record event, recording stopped");
+            self.failed = true;
+        }
+    }
+
+    /// Flushes the recording
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Raises the events of a recording into a dispatcher,
+/// at the same ticks they were recorded at
+pub struct EventReplayer {
+    buffer: NetworkBuffer,
+    start_tick: u64,
+    include_derived: bool,
+}
+
+impl EventReplayer {
+    /// Opens a recording file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        let mut data = Vec::new();
+        File::open(path)?.read_to_end(&mut data)?;
+        Self::from_bytes(data)
+    }
+
+    /// Reads a recording from memory
+    ///
+    /// # Returns
+    /// * The replayer, or an error if the data is not a recording
+    /// or was recorded with a different event registry
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, ReplayError> {
+        let mut buffer = NetworkBuffer::from_bytes(data);
+        let magic = buffer
+            .read_bytes(MAGIC.len())
+            .map_err(|_| ReplayError::InvalidHeader)?;
+        if magic != MAGIC {
+            return Err(ReplayError::InvalidHeader);
+        }
+
+        let version = buffer.read_u16()?;
+        if version != VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let recorded = buffer.read_u64()?;
+        let current = event_registry::registry_fingerprint();
+        if recorded != current {
+            return Err(ReplayError::FingerprintMismatch { recorded, current });
+        }
+
+        Ok(Self {
+            buffer,
+            start_tick: 0,
+            include_derived: false,
+        })
+    }
+
+    /// Also replays the events that listeners raised while dispatching other events.
+    ///
+    /// By default they are skipped, since the listeners of the
+    /// replaying dispatcher are expected to raise them again
+    pub fn with_derived_events(mut self) -> Self {
+        self.include_derived = true;
+        self
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.buffer.remaining() == 0
+    }
+
+    pub(crate) fn set_start_tick(&mut self, tick: u64) {
+        self.start_tick = tick;
+    }
+
+    /// Reads every event recorded up to the given tick
+    ///
+    /// # Arguments
+    /// * `tick` - The current tick of the dispatcher
+    ///
+    /// # Returns
+    /// * The events to raise
+    pub(crate) fn take_due(&mut self, tick: u64) -> Vec<Box<dyn GameEvent>> {
+        let tick = tick.saturating_sub(self.start_tick);
+        let mut events = Vec::new();
+
+        while !self.is_finished() {
+            // Peek the tick of the next record
+            let position = self.buffer.get_position();
+            let next = match self.buffer.read_u64() {
+                Ok(next) => next,
+                Err(error) => {
+                    self.abort(error);
+                    break;
+                }
+            };
+            if next > tick {
+                self.buffer.set_position(position);
+                break;
+            }
+
+            let derived = match self.buffer.read_bool() {
+                Ok(derived) => derived,
+                Err(error) => {
+                    self.abort(error);
+                    break;
+                }
+            };
+            match event_registry::deserialize_event(&mut self.buffer) {
+                Ok(event) => {
+                    if self.include_derived || !derived {
+                        events.push(event);
+                    }
+                }
+                Err(BufferError::UnknownEventType(id)) => {
+                    log!(
+                        Warning,
+                        "Skipped recorded event of unknown type {:#018x}",
+                        id
+                    );
+                }
+                Err(error) => {
+                    self.abort(error);
+                    break;
+                }
+            }
+        }
+        events
+    }
+
+    fn abort(&mut self, error: BufferError) {
+        log!(Error, &error, "Corrupted recording, replay stopped");
+        let end = self.buffer.len();
+        self.buffer.set_position(end);
+    }
+}
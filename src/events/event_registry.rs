@@ -1,18 +1,64 @@
+use crate::events::event_route::EventRoute;
 use crate::events::GameEvent;
+use crate::networking::network_buffer::{BufferError, BufferResult, NetworkBuffer};
 use crate::utils::id_type::id_type;
 use once_cell::sync::Lazy;
 use std::any::TypeId;
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
 use std::hash::Hasher;
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
 
+/// Registers a type as a [`GameEvent`].
+///
+/// The id of the event type is derived from its registered name, which defaults
+/// to the full path of the type. Both can be overridden, so that ids stay the
+/// same when the type is moved or renamed. Events stay local unless a
+/// [`route`](crate::events::EventRoute) is given. Fields listed in `replicate`
+/// are serialized when the event is sent over the network, and must
+/// implement [`Serializable`](crate::networking::Serializable):
+///
+/// `register_game_event!(MyEvent, data -> event_data, new -> new_empty, name -> "my_event");`
+///
+/// `register_game_event!(MyEvent, data -> event_data, new -> new_empty, id -> 42, route -> ToServer, replicate -> [value]);`
 #[macro_export]
 macro_rules! register_game_event {
-    ($name:ident, data -> $event_data:ident, new -> $constructor:ident) => {
+    (@name $name:ident) => {
+        concat!(module_path!(), "::", stringify!($name))
+    };
+    (@name $name:ident, $registered_name:expr) => {
+        $registered_name
+    };
+    (@id) => {
+        None
+    };
+    (@id, $id:expr) => {
+        Some($id)
+    };
+    (@route) => {
+        $crate::events::EventRoute::LocalOnly
+    };
+    (@route, $route:ident) => {
+        $crate::events::EventRoute::$route
+    };
+    (
+        $name:ident, data -> $event_data:ident, new -> $constructor:ident
+        $(, name -> $registered_name:expr)?
+        $(, id -> $id:expr)?
+        $(, route -> $route:ident)?
+        $(, replicate -> [$($field:ident),* $(,)?])?
+    ) => {
         #[ctor::ctor]
         #[allow(non_snake_case)]
         fn $name() {
-            $crate::events::event_registry::register_event_type::<$name>(<$name>::$constructor);
+            // Panicking before main would abort the process, collisions are reported later
+            $crate::events::event_registry::register_startup_event_type::<$name>(
+                $crate::register_game_event!(@name $name $(, $registered_name)?),
+                $crate::register_game_event!(@id $(, $id)?),
+                $crate::register_game_event!(@route $(, $route)?),
+                <$name>::$constructor,
+            );
         }
 
         impl $crate::events::game_event::GameEvent for $name {
@@ -26,45 +72,197 @@ macro_rules! register_game_event {
                 let id = $crate::events::event_registry::get_event_type_of::<$name>();
                 id.unwrap()
             }
+            #[allow(unused_variables)]
+            fn write_fields(&self, buffer: &mut $crate::networking::NetworkBuffer) {
+                $($($crate::networking::Serializable::write(&self.$field, buffer);)*)?
+            }
+            #[allow(unused_variables)]
+            fn read_fields(
+                &mut self,
+                buffer: &mut $crate::networking::NetworkBuffer,
+            ) -> Result<(), $crate::networking::BufferError> {
+                $($(self.$field = $crate::networking::Serializable::read(buffer)?;)*)?
+                Ok(())
+            }
         }
     };
 }
 
 id_type!(EventType);
 
+impl EventType {
+    /// The numeric id of the event type, the same in every build
+    /// registering the event type with the same name or id
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    /// Retrieves a registered event type from its numeric id
+    ///
+    /// # Returns
+    /// * The event type, or `None` if no event type has that id
+    pub fn from_id(id: u64) -> Option<EventType> {
+        let event_type = EventType::from(id);
+        if METADATA.read().unwrap().contains_key(&event_type) {
+            Some(event_type)
+        } else {
+            None
+        }
+    }
+}
+
+impl Debug for EventType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EventType({:#018x})", self.id)
+    }
+}
+
 pub struct EventTypeMetadata {
     pub constructor: fn() -> Box<dyn GameEvent>,
     pub event_type: EventType,
     pub type_id: TypeId,
     pub name: String,
+    pub route: EventRoute,
+}
+
+/// Why an event type couldn't be registered
+#[derive(Clone, Debug)]
+pub enum RegistrationError {
+    AlreadyRegistered(String),
+    NameCollision(String),
+    IdCollision {
+        id: u64,
+        existing: String,
+        new: String,
+    },
 }
 
-static METADATA: RwLock<Vec<EventTypeMetadata>> = RwLock::new(Vec::new());
+impl Error for RegistrationError {}
+
+impl Display for RegistrationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistrationError::AlreadyRegistered(name) => {
+                write!(f, "Event type {} is already registered", name)
+            }
+            RegistrationError::NameCollision(name) => {
+                write!(f, "Two event types are registered as {}", name)
+            }
+            RegistrationError::IdCollision { id, existing, new } => write!(
+                f,
+                "Event types {} and {} share the id {:#018x}",
+                existing, new, id
+            ),
+        }
+    }
+}
+
+static METADATA: Lazy<RwLock<HashMap<EventType, EventTypeMetadata>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
 static TYPE_TO_EVENT_TYPE: Lazy<RwLock<HashMap<TypeId, EventType>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
+// Errors of the registrations done while the program starts
+static STARTUP_ERRORS: Mutex<Vec<RegistrationError>> = Mutex::new(Vec::new());
+
+static FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+static FNV_PRIME: u64 = 0x100000001b3;
 
-pub fn register_event_type<T>(constructor: fn() -> Box<dyn GameEvent>) -> EventType
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Computes the id an event type registered with the given name gets by default
+pub fn event_type_id_of_name(name: &str) -> u64 {
+    fnv1a(FNV_OFFSET_BASIS, name.as_bytes())
+}
+
+/// Registers a type as an event type. Usually called through [`register_game_event`]
+///
+/// # Arguments
+/// * `name` - The name of the event type, unique among event types
+/// * `id` - The id of the event type, or `None` to derive it from the name
+/// * `route` - Where events of this type travel once dispatched
+/// * `constructor` - Creates an empty event of this type
+///
+/// # Returns
+/// * The event type, or the reason it collides with an already registered one
+pub fn register_event_type<T>(
+    name: &str,
+    id: Option<u64>,
+    route: EventRoute,
+    constructor: fn() -> Box<dyn GameEvent>,
+) -> Result<EventType, RegistrationError>
 where
     T: GameEvent,
 {
+    let event_type = EventType::from(id.unwrap_or_else(|| event_type_id_of_name(name)));
+
     let mut metadata = METADATA.write().unwrap();
-    let id = EventType::from(metadata.len() as u64);
-
-    // Construct metadata
-    let data = EventTypeMetadata {
-        constructor,
-        event_type: id,
-        type_id: TypeId::of::<T>(),
-        name: std::any::type_name::<T>().to_string(),
-    };
+    let mut type_to_event_type = TYPE_TO_EVENT_TYPE.write().unwrap();
+
+    if type_to_event_type.contains_key(&TypeId::of::<T>()) {
+        return Err(RegistrationError::AlreadyRegistered(name.to_string()));
+    }
+    if let Some(existing) = metadata.get(&event_type) {
+        return Err(RegistrationError::IdCollision {
+            id: event_type.id,
+            existing: existing.name.clone(),
+            new: name.to_string(),
+        });
+    }
+    if metadata.values().any(|data| data.name == name) {
+        return Err(RegistrationError::NameCollision(name.to_string()));
+    }
 
-    metadata.push(data);
-    drop(metadata);
+    metadata.insert(
+        event_type,
+        EventTypeMetadata {
+            constructor,
+            event_type,
+            type_id: TypeId::of::<T>(),
+            name: name.to_string(),
+            route,
+        },
+    );
+    type_to_event_type.insert(TypeId::of::<T>(), event_type);
 
-    let mut type_to_event_type = TYPE_TO_EVENT_TYPE.write().unwrap();
-    type_to_event_type.insert(TypeId::of::<T>(), id);
+    Ok(event_type)
+}
+
+/// Same as [`register_event_type`], recording errors for [`check_registrations`] instead of
+/// returning them. Called by [`register_game_event`] while the program starts
+#[doc(hidden)]
+pub fn register_startup_event_type<T>(
+    name: &str,
+    id: Option<u64>,
+    route: EventRoute,
+    constructor: fn() -> Box<dyn GameEvent>,
+) where
+    T: GameEvent,
+{
+    if let Err(error) = register_event_type::<T>(name, id, route, constructor) {
+        STARTUP_ERRORS.lock().unwrap().push(error);
+    }
+}
 
-    id
+/// Checks that every event type registered through [`register_game_event`]
+/// got its own name and id. A collision is a programming error,
+/// the event system can't work with it
+///
+/// # Returns
+/// * Nothing, or the first registration that failed
+pub fn check_registrations() -> Result<(), RegistrationError> {
+    match STARTUP_ERRORS.lock().unwrap().first() {
+        Some(error) => Err(error.clone()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn clear_startup_errors() {
+    STARTUP_ERRORS.lock().unwrap().clear();
 }
 
 pub fn get_event_type(type_id: &TypeId) -> Option<EventType> {
@@ -78,19 +276,112 @@ where
     get_event_type(&TypeId::of::<T>())
 }
 
+/// Finds an event type from its registered name
+pub fn get_event_type_by_name(name: &str) -> Option<EventType> {
+    METADATA
+        .read()
+        .unwrap()
+        .values()
+        .find(|data| data.name == name)
+        .map(|data| data.event_type)
+}
+
+pub fn get_event_name(event_type: EventType) -> Option<String> {
+    METADATA
+        .read()
+        .unwrap()
+        .get(&event_type)
+        .map(|data| data.name.clone())
+}
+
+/// Retrieves the route of an event type
+///
+/// # Returns
+/// * The route, or [`EventRoute::LocalOnly`] if the event type is not registered
+pub fn get_event_route(event_type: EventType) -> EventRoute {
+    METADATA
+        .read()
+        .unwrap()
+        .get(&event_type)
+        .map(|data| data.route)
+        .unwrap_or(EventRoute::LocalOnly)
+}
+
 pub fn get_event_constructor(event_type: EventType) -> Option<fn() -> Box<dyn GameEvent>> {
     METADATA
         .read()
         .unwrap()
-        .get(event_type.id as usize)
+        .get(&event_type)
         .map(|data| data.constructor)
 }
 
+/// Calls `f` on every registered event type, in order of id
 pub fn with_event_types<T>(f: T)
 where
     T: Fn(&EventTypeMetadata),
 {
-    for metadata in METADATA.read().unwrap().iter() {
-        f(&metadata);
+    let metadata = METADATA.read().unwrap();
+    let mut sorted: Vec<&EventTypeMetadata> = metadata.values().collect();
+    sorted.sort_by_key(|data| data.event_type.id);
+    for data in sorted {
+        f(data);
     }
 }
+
+/// Computes a hash of every registered event type, its name and its route.
+///
+/// Two peers with the same fingerprint agree on the meaning and route
+/// of every event type id, and can safely exchange events
+pub fn registry_fingerprint() -> u64 {
+    let metadata = METADATA.read().unwrap();
+    let mut sorted: Vec<&EventTypeMetadata> = metadata.values().collect();
+    sorted.sort_by_key(|data| data.event_type.id);
+
+    sorted.iter().fold(FNV_OFFSET_BASIS, |hash, data| {
+        let hash = fnv1a(hash, &data.event_type.id.to_le_bytes());
+        let hash = fnv1a(hash, &[data.route.to_u8()]);
+        let hash = fnv1a(hash, data.name.as_bytes());
+        // Separates names, so that moving characters between them changes the hash
+        fnv1a(hash, &[0])
+    })
+}
+
+/// Writes an event to a buffer, so that it can be reconstructed by
+/// [`deserialize_event`] on a peer with the same registry fingerprint
+///
+/// # Arguments
+/// * `event` - The event
+/// * `buffer` - The buffer to write to
+pub fn serialize_event(event: &dyn GameEvent, buffer: &mut NetworkBuffer) {
+    let mut payload = NetworkBuffer::new();
+    event.get_event_data().write(&mut payload);
+    event.write_fields(&mut payload);
+
+    // The length allows skipping events of unknown types
+    buffer.write_u64(event.get_event_type().id);
+    buffer.write_u32(payload.len() as u32);
+    buffer.write_bytes(payload.as_bytes());
+}
+
+/// Reads an event written by [`serialize_event`]
+///
+/// # Arguments
+/// * `buffer` - The buffer to read from
+///
+/// # Returns
+/// * The event, or the reason it couldn't be read. Events of unknown
+/// types are skipped, so that the next one can still be read
+pub fn deserialize_event(buffer: &mut NetworkBuffer) -> BufferResult<Box<dyn GameEvent>> {
+    let id = buffer.read_u64()?;
+    let length = buffer.read_u32()? as usize;
+    let mut payload = NetworkBuffer::from_bytes(buffer.read_bytes(length)?.to_vec());
+
+    let constructor = match get_event_constructor(EventType::from(id)) {
+        Some(constructor) => constructor,
+        None => return Err(BufferError::UnknownEventType(id)),
+    };
+    let mut event = constructor();
+    event.get_event_data_mut().read(&mut payload)?;
+    event.read_fields(&mut payload)?;
+    Ok(event)
+}
@@ -0,0 +1,95 @@
+use crate::events::event_registry::EventType;
+use crate::networking::network_buffer::{BufferError, NetworkBuffer};
+use crate::networking::token::Token;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Where events of a type travel once they have been dispatched locally.
+///
+/// Every peer must agree on the route of each event type,
+/// which is why it is part of the registry fingerprint
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum EventRoute {
+    /// Never leaves the dispatcher it was raised on
+    LocalOnly,
+    /// Raised on a client, sent to the server
+    ToServer,
+    /// Raised on the server, sent to every client
+    ToAllClients,
+    /// Raised on the server, sent to the client owning the event
+    ToOwner,
+}
+
+impl EventRoute {
+    pub(crate) fn to_u8(self) -> u8 {
+        match self {
+            EventRoute::LocalOnly => 0,
+            EventRoute::ToServer => 1,
+            EventRoute::ToAllClients => 2,
+            EventRoute::ToOwner => 3,
+        }
+    }
+
+    /// Whether events on this route are sent by clients, as opposed to the server
+    pub fn is_sent_by_client(self) -> bool {
+        self == EventRoute::ToServer
+    }
+
+    /// Whether events on this route are sent by the server, as opposed to clients
+    pub fn is_sent_by_server(self) -> bool {
+        self == EventRoute::ToAllClients || self == EventRoute::ToOwner
+    }
+}
+
+/// The peer an event is sent to
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum EventTarget {
+    Server,
+    AllClients,
+    Client(Token),
+}
+
+/// Delivers serialized events to other peers.
+///
+/// The receiving peer passes the buffer to
+/// [`EventDispatcher::receive_remote_event`](crate::events::EventDispatcher::receive_remote_event)
+pub trait EventTransport: Send + Sync {
+    fn send(&self, target: EventTarget, buffer: NetworkBuffer);
+}
+
+/// Why an event received from another peer was rejected
+#[derive(Debug)]
+pub enum RoutingError {
+    /// The event claims to come from a peer it can't come from
+    SpoofedSource(EventType),
+    /// The route of the event type doesn't allow it to travel in this direction
+    WrongDirection(EventType),
+    /// The server received an event without knowing which client sent it
+    UnknownSender,
+    BufferError(BufferError),
+}
+
+impl Error for RoutingError {}
+
+impl Display for RoutingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoutingError::SpoofedSource(event_type) => {
+                write!(f, "Event of type {:?} has a spoofed source", event_type)
+            }
+            RoutingError::WrongDirection(event_type) => write!(
+                f,
+                "Events of type {:?} can't be received by this peer",
+                event_type
+            ),
+            RoutingError::UnknownSender => write!(f, "The sender of the event is unknown"),
+            RoutingError::BufferError(error) => write!(f, "BufferError: {}", error),
+        }
+    }
+}
+
+impl From<BufferError> for RoutingError {
+    fn from(error: BufferError) -> Self {
+        RoutingError::BufferError(error)
+    }
+}
@@ -1,6 +1,8 @@
 use crate::events::event_registry::EventType;
 use crate::events::game_event::EventSource::*;
+use crate::networking::network_buffer::{BufferError, BufferResult, NetworkBuffer};
 use crate::networking::replicate::Replicate;
+use crate::networking::token::Token;
 use crate::utils::id_type::id_type;
 use mopa::mopafy;
 use std::hash::Hasher;
@@ -17,6 +19,7 @@ pub enum EventSource {
 pub struct EventData {
     id: EventId,
     from: EventSource,
+    owner: Option<Token>,
     cancelled: bool,
 }
 
@@ -25,6 +28,7 @@ impl EventData {
         Self {
             id: EventId::new(),
             from: NotSet,
+            owner: None,
             cancelled: false,
         }
     }
@@ -41,32 +45,88 @@ impl EventData {
     pub fn get_id(&self) -> &EventId {
         &self.id
     }
+
+    /// Sets the client the event belongs to.
+    ///
+    /// Events routed [`ToOwner`](crate::events::EventRoute::ToOwner) are sent to it,
+    /// and events received by the server belong to the client that sent them
+    pub fn set_owner(&mut self, owner: Option<Token>) {
+        self.owner = owner;
+    }
+    pub fn get_owner(&self) -> Option<Token> {
+        self.owner
+    }
     pub fn set_cancelled(&mut self, cancelled: bool) {
         self.cancelled = cancelled;
     }
     pub fn is_cancelled(&self) -> bool {
         self.cancelled
     }
+
+    pub fn write(&self, buffer: &mut NetworkBuffer) {
+        buffer.write_u64(self.id.id);
+        buffer.write_u8(match self.from {
+            NotSet => 0,
+            Client => 1,
+            Server => 2,
+        });
+        match self.owner {
+            Some(owner) => {
+                buffer.write_bool(true);
+                buffer.write_u64(owner.get_id());
+            }
+            None => buffer.write_bool(false),
+        }
+        buffer.write_bool(self.cancelled);
+    }
+
+    pub fn read(&mut self, buffer: &mut NetworkBuffer) -> BufferResult<()> {
+        self.id = EventId::from(buffer.read_u64()?);
+        self.from = match buffer.read_u8()? {
+            0 => NotSet,
+            1 => Client,
+            2 => Server,
+            value => {
+                return Err(BufferError::InvalidData(format!(
+                    "{} is not an event source",
+                    value
+                )))
+            }
+        };
+        self.owner = if buffer.read_bool()? {
+            Some(Token::from_id(buffer.read_u64()?))
+        } else {
+            None
+        };
+        self.cancelled = buffer.read_bool()?;
+        Ok(())
+    }
 }
 
 pub trait GameEvent: mopa::Any + Send {
     fn get_event_data(&self) -> &EventData;
     fn get_event_data_mut(&mut self) -> &mut EventData;
     fn get_event_type(&self) -> EventType;
+
+    /// Writes the fields that replicate, as declared when registering the event type
+    fn write_fields(&self, _buffer: &mut NetworkBuffer) {}
+
+    /// Reads back the fields written by [`GameEvent::write_fields`]
+    fn read_fields(&mut self, _buffer: &mut NetworkBuffer) -> BufferResult<()> {
+        Ok(())
+    }
 }
 
 mopafy!(GameEvent);
 
 impl<T: GameEvent> Replicate for T {
-    fn write_data(&self, _: bool) {
-        unimplemented!();
-    }
-
-    fn read_data(&mut self, _: bool) {
-        unimplemented!();
+    fn write_data(&self, buffer: &mut NetworkBuffer, _: bool) {
+        self.get_event_data().write(buffer);
+        self.write_fields(buffer);
     }
 
-    fn is_local(&self) -> bool {
-        true
+    fn read_data(&mut self, buffer: &mut NetworkBuffer, _: bool) -> BufferResult<()> {
+        self.get_event_data_mut().read(buffer)?;
+        self.read_fields(buffer)
     }
 }
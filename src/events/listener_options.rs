@@ -0,0 +1,59 @@
+use crate::utils::PanicPolicy;
+
+/// The order in which listeners receive an event.
+///
+/// Lower priorities are called first, so that higher priorities
+/// have the final say on the outcome of the event.
+/// Monitor listeners are called last and should only observe the event
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ListenerPriority {
+    Lowest,
+    Low,
+    Normal,
+    High,
+    Highest,
+    Monitor,
+}
+
+/// How a listener is registered with an [`EventDispatcher`](crate::events::EventDispatcher)
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct ListenerOptions {
+    pub priority: ListenerPriority,
+    pub receive_cancelled: bool,
+    pub panic_policy: PanicPolicy,
+    pub once: bool,
+}
+
+impl ListenerOptions {
+    /// Normal priority, not receiving cancelled events, unregistered if it panics
+    pub fn new() -> Self {
+        Self {
+            priority: ListenerPriority::Normal,
+            receive_cancelled: false,
+            panic_policy: PanicPolicy::Unregister,
+            once: false,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: ListenerPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// The listener will still be called once an event has been cancelled
+    pub fn receiving_cancelled(mut self) -> Self {
+        self.receive_cancelled = true;
+        self
+    }
+
+    pub fn with_panic_policy(mut self, panic_policy: PanicPolicy) -> Self {
+        self.panic_policy = panic_policy;
+        self
+    }
+
+    /// The listener is unregistered after it receives its first event
+    pub fn once(mut self) -> Self {
+        self.once = true;
+        self
+    }
+}
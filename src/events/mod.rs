@@ -1,7 +1,11 @@
 pub mod event_dispatcher;
+pub mod event_interceptor;
 pub mod event_listener;
+pub mod event_recording;
 pub mod event_registry;
+pub mod event_route;
 pub mod game_event;
+pub mod listener_options;
 pub mod nothing_happened_event;
 
 pub use game_event::EventData;
@@ -9,9 +13,33 @@ pub use game_event::EventSource;
 pub use game_event::GameEvent;
 
 pub use event_listener::EventListener;
+pub use event_listener::TypedEventListener;
+pub use listener_options::ListenerOptions;
+pub use listener_options::ListenerPriority;
 
+pub use event_dispatcher::EventDelay;
 pub use event_dispatcher::EventDispatcher;
 pub use event_dispatcher::EventRequestHandle;
+pub use event_dispatcher::InterceptorHandle;
+pub use event_dispatcher::ListenerFailure;
+pub use event_dispatcher::ListenerGuard;
 pub use event_dispatcher::ListenerHandle;
+pub use event_dispatcher::ScheduledEventHandle;
+
+pub use event_interceptor::DebugTracer;
+pub use event_interceptor::EventInterceptor;
+pub use event_interceptor::EventSpy;
+pub use event_interceptor::InterceptAction;
+pub use event_interceptor::InterceptContext;
+pub use event_interceptor::RateLimiter;
+
+pub use event_recording::EventRecorder;
+pub use event_recording::EventReplayer;
+pub use event_recording::ReplayError;
+
+pub use event_route::EventRoute;
+pub use event_route::EventTarget;
+pub use event_route::EventTransport;
+pub use event_route::RoutingError;
 
 pub use nothing_happened_event::NothingHappenedEvent;
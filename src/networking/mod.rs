@@ -1,2 +1,9 @@
+pub mod network_buffer;
 pub mod replicate;
+pub mod serializable;
 pub mod token;
+
+pub use network_buffer::BufferError;
+pub use network_buffer::NetworkBuffer;
+pub use replicate::Replicate;
+pub use serializable::Serializable;
@@ -0,0 +1,211 @@
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Why data couldn't be read from a [`NetworkBuffer`]
+#[derive(Debug)]
+pub enum BufferError {
+    UnexpectedEnd,
+    InvalidData(String),
+    UnknownEventType(u64),
+}
+
+impl Error for BufferError {}
+
+impl Display for BufferError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BufferError::UnexpectedEnd => write!(f, "Unexpected end of buffer"),
+            BufferError::InvalidData(message) => write!(f, "Invalid data: {}", message),
+            BufferError::UnknownEventType(id) => write!(f, "Unknown event type {:#018x}", id),
+        }
+    }
+}
+
+pub type BufferResult<T> = Result<T, BufferError>;
+
+/// A growable byte buffer that values are written to and read back
+/// from in order. Every number is stored in little endian
+pub struct NetworkBuffer {
+    data: Vec<u8>,
+    position: usize,
+}
+
+impl NetworkBuffer {
+    pub fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Wraps received bytes, to be read from the start
+    pub fn from_bytes(data: Vec<u8>) -> Self {
+        Self { data, position: 0 }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The position the next value will be read from
+    pub fn get_position(&self) -> usize {
+        self.position
+    }
+
+    /// Moves the position the next value will be read from
+    pub fn set_position(&mut self, position: usize) {
+        self.position = position.min(self.data.len());
+    }
+
+    /// The amount of bytes left to read
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.position = 0;
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    /// Reads the given amount of bytes
+    ///
+    /// # Returns
+    /// * The bytes, or an error if the buffer is too short
+    pub fn read_bytes(&mut self, count: usize) -> BufferResult<&[u8]> {
+        if self.remaining() < count {
+            return Err(BufferError::UnexpectedEnd);
+        }
+        let bytes = &self.data[self.position..self.position + count];
+        self.position += count;
+        Ok(bytes)
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn read_u8(&mut self) -> BufferResult<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn read_u16(&mut self) -> BufferResult<u16> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn read_u32(&mut self) -> BufferResult<u32> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn read_u64(&mut self) -> BufferResult<u64> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn write_i8(&mut self, value: i8) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn read_i8(&mut self) -> BufferResult<i8> {
+        Ok(i8::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn write_i16(&mut self, value: i16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn read_i16(&mut self) -> BufferResult<i16> {
+        Ok(i16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn read_i32(&mut self) -> BufferResult<i32> {
+        Ok(i32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn write_i64(&mut self, value: i64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn read_i64(&mut self) -> BufferResult<i64> {
+        Ok(i64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn read_f32(&mut self) -> BufferResult<f32> {
+        Ok(f32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn read_f64(&mut self) -> BufferResult<f64> {
+        Ok(f64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn read_bool(&mut self) -> BufferResult<bool> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(BufferError::InvalidData(format!(
+                "{} is not a boolean",
+                value
+            ))),
+        }
+    }
+
+    /// Writes the length of the string, followed by its UTF-8 bytes
+    pub fn write_string(&mut self, value: &str) {
+        self.write_u32(value.len() as u32);
+        self.write_bytes(value.as_bytes());
+    }
+
+    pub fn read_string(&mut self) -> BufferResult<String> {
+        let length = self.read_u32()? as usize;
+        let bytes = self.read_bytes(length)?.to_vec();
+        String::from_utf8(bytes).map_err(|error| BufferError::InvalidData(error.to_string()))
+    }
+
+    fn read_array<const N: usize>(&mut self) -> BufferResult<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+}
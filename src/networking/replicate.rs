@@ -1,7 +1,8 @@
+use crate::networking::network_buffer::{BufferResult, NetworkBuffer};
+
 pub trait Replicate {
-    // TODO ADD PARAMETERS
-    fn write_data(&self, is_client: bool);
-    fn read_data(&mut self, is_client: bool);
+    fn write_data(&self, buffer: &mut NetworkBuffer, is_client: bool);
+    fn read_data(&mut self, buffer: &mut NetworkBuffer, is_client: bool) -> BufferResult<()>;
 
     fn needs_replication(&self) -> bool {
         true
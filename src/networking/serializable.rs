@@ -0,0 +1,272 @@
+use crate::networking::network_buffer::{BufferError, BufferResult, NetworkBuffer};
+use crate::settings::Setting;
+use crate::utils::logger::Severity;
+#[cfg(feature = "window")]
+use crate::window::VsyncMode;
+use cgmath::{Vector2, Vector3, Vector4};
+
+/// A value that can be written to a [`NetworkBuffer`] and read back
+pub trait Serializable: Sized {
+    fn write(&self, buffer: &mut NetworkBuffer);
+    fn read(buffer: &mut NetworkBuffer) -> BufferResult<Self>;
+}
+
+macro_rules! serializable_number {
+    ($type:ty, $write:ident, $read:ident) => {
+        impl Serializable for $type {
+            fn write(&self, buffer: &mut NetworkBuffer) {
+                buffer.$write(*self);
+            }
+
+            fn read(buffer: &mut NetworkBuffer) -> BufferResult<Self> {
+                buffer.$read()
+            }
+        }
+    };
+}
+
+serializable_number!(u8, write_u8, read_u8);
+serializable_number!(u16, write_u16, read_u16);
+serializable_number!(u32, write_u32, read_u32);
+serializable_number!(u64, write_u64, read_u64);
+serializable_number!(i8, write_i8, read_i8);
+serializable_number!(i16, write_i16, read_i16);
+serializable_number!(i32, write_i32, read_i32);
+serializable_number!(i64, write_i64, read_i64);
+serializable_number!(f32, write_f32, read_f32);
+serializable_number!(f64, write_f64, read_f64);
+serializable_number!(bool, write_bool, read_bool);
+
+impl Serializable for String {
+    fn write(&self, buffer: &mut NetworkBuffer) {
+        buffer.write_string(self);
+    }
+
+    fn read(buffer: &mut NetworkBuffer) -> BufferResult<Self> {
+        buffer.read_string()
+    }
+}
+
+impl<T: Serializable> Serializable for Vec<T> {
+    fn write(&self, buffer: &mut NetworkBuffer) {
+        buffer.write_u32(self.len() as u32);
+        for value in self.iter() {
+            value.write(buffer);
+        }
+    }
+
+    fn read(buffer: &mut NetworkBuffer) -> BufferResult<Self> {
+        let length = buffer.read_u32()? as usize;
+        // The length comes from the network, don't trust it for the allocation
+        let mut values = Vec::with_capacity(length.min(buffer.remaining()));
+        for _ in 0..length {
+            values.push(T::read(buffer)?);
+        }
+        Ok(values)
+    }
+}
+
+impl<T: Serializable> Serializable for Option<T> {
+    fn write(&self, buffer: &mut NetworkBuffer) {
+        match self {
+            Some(value) => {
+                buffer.write_bool(true);
+                value.write(buffer);
+            }
+            None => buffer.write_bool(false),
+        }
+    }
+
+    fn read(buffer: &mut NetworkBuffer) -> BufferResult<Self> {
+        if buffer.read_bool()? {
+            Ok(Some(T::read(buffer)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Serializable> Serializable for Vector2<T> {
+    fn write(&self, buffer: &mut NetworkBuffer) {
+        self.x.write(buffer);
+        self.y.write(buffer);
+    }
+
+    fn read(buffer: &mut NetworkBuffer) -> BufferResult<Self> {
+        Ok(Vector2::new(T::read(buffer)?, T::read(buffer)?))
+    }
+}
+
+impl<T: Serializable> Serializable for Vector3<T> {
+    fn write(&self, buffer: &mut NetworkBuffer) {
+        self.x.write(buffer);
+        self.y.write(buffer);
+        self.z.write(buffer);
+    }
+
+    fn read(buffer: &mut NetworkBuffer) -> BufferResult<Self> {
+        Ok(Vector3::new(
+            T::read(buffer)?,
+            T::read(buffer)?,
+            T::read(buffer)?,
+        ))
+    }
+}
+
+impl<T: Serializable> Serializable for Vector4<T> {
+    fn write(&self, buffer: &mut NetworkBuffer) {
+        self.x.write(buffer);
+        self.y.write(buffer);
+        self.z.write(buffer);
+        self.w.write(buffer);
+    }
+
+    fn read(buffer: &mut NetworkBuffer) -> BufferResult<Self> {
+        Ok(Vector4::new(
+            T::read(buffer)?,
+            T::read(buffer)?,
+            T::read(buffer)?,
+            T::read(buffer)?,
+        ))
+    }
+}
+
+impl Serializable for Severity {
+    fn write(&self, buffer: &mut NetworkBuffer) {
+        buffer.write_u8(match self {
+            Severity::Unknown => 0,
+            Severity::Debug => 1,
+            Severity::Info => 2,
+            Severity::Loading => 3,
+            Severity::Warning => 4,
+            Severity::Error => 5,
+            Severity::Fatal => 6,
+        });
+    }
+
+    fn read(buffer: &mut NetworkBuffer) -> BufferResult<Self> {
+        match buffer.read_u8()? {
+            0 => Ok(Severity::Unknown),
+            1 => Ok(Severity::Debug),
+            2 => Ok(Severity::Info),
+            3 => Ok(Severity::Loading),
+            4 => Ok(Severity::Warning),
+            5 => Ok(Severity::Error),
+            6 => Ok(Severity::Fatal),
+            value => Err(BufferError::InvalidData(format!(
+                "{} is not a severity",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "window")]
+impl Serializable for VsyncMode {
+    fn write(&self, buffer: &mut NetworkBuffer) {
+        buffer.write_u8(match self {
+            VsyncMode::Disabled => 0,
+            VsyncMode::Enabled => 1,
+            VsyncMode::Adaptive => 2,
+        });
+    }
+
+    fn read(buffer: &mut NetworkBuffer) -> BufferResult<Self> {
+        match buffer.read_u8()? {
+            0 => Ok(VsyncMode::Disabled),
+            1 => Ok(VsyncMode::Enabled),
+            2 => Ok(VsyncMode::Adaptive),
+            value => Err(BufferError::InvalidData(format!(
+                "{} is not a vsync mode",
+                value
+            ))),
+        }
+    }
+}
+
+impl Serializable for Setting {
+    fn write(&self, buffer: &mut NetworkBuffer) {
+        match self {
+            Setting::Empty => buffer.write_u8(0),
+            Setting::Boolean(value) => {
+                buffer.write_u8(1);
+                value.write(buffer);
+            }
+            Setting::Str(value) => {
+                buffer.write_u8(2);
+                value.write(buffer);
+            }
+            Setting::UnsignedInt(value) => {
+                buffer.write_u8(3);
+                value.write(buffer);
+            }
+            Setting::SignedInt(value) => {
+                buffer.write_u8(4);
+                value.write(buffer);
+            }
+            Setting::FloatingPoint(value) => {
+                buffer.write_u8(5);
+                value.write(buffer);
+            }
+            Setting::IVector2(value) => {
+                buffer.write_u8(6);
+                value.write(buffer);
+            }
+            Setting::FVector2(value) => {
+                buffer.write_u8(7);
+                value.write(buffer);
+            }
+            Setting::IVector3(value) => {
+                buffer.write_u8(8);
+                value.write(buffer);
+            }
+            Setting::FVector3(value) => {
+                buffer.write_u8(9);
+                value.write(buffer);
+            }
+            Setting::IVector4(value) => {
+                buffer.write_u8(10);
+                value.write(buffer);
+            }
+            Setting::FVector4(value) => {
+                buffer.write_u8(11);
+                value.write(buffer);
+            }
+            Setting::LogSeverity(value) => {
+                buffer.write_u8(12);
+                value.write(buffer);
+            }
+            #[cfg(feature = "window")]
+            Setting::Vsync(value) => {
+                buffer.write_u8(13);
+                value.write(buffer);
+            }
+        }
+    }
+
+    fn read(buffer: &mut NetworkBuffer) -> BufferResult<Self> {
+        Ok(match buffer.read_u8()? {
+            0 => Setting::Empty,
+            1 => Setting::Boolean(Serializable::read(buffer)?),
+            2 => Setting::Str(Serializable::read(buffer)?),
+            3 => Setting::UnsignedInt(Serializable::read(buffer)?),
+            4 => Setting::SignedInt(Serializable::read(buffer)?),
+            5 => Setting::FloatingPoint(Serializable::read(buffer)?),
+            6 => Setting::IVector2(Serializable::read(buffer)?),
+            7 => Setting::FVector2(Serializable::read(buffer)?),
+            8 => Setting::IVector3(Serializable::read(buffer)?),
+            9 => Setting::FVector3(Serializable::read(buffer)?),
+            10 => Setting::IVector4(Serializable::read(buffer)?),
+            11 => Setting::FVector4(Serializable::read(buffer)?),
+            12 => Setting::LogSeverity(Serializable::read(buffer)?),
+            #[cfg(feature = "window")]
+            13 => Setting::Vsync(Serializable::read(buffer)?),
+            value => {
+                return Err(BufferError::InvalidData(format!(
+                    "{} is not a setting type",
+                    value
+                )))
+            }
+        })
+    }
+}
@@ -1,3 +1,18 @@
 use crate::utils::id_type::id_type;
 
 id_type!(Token);
+
+impl Token {
+    /// Generates a token, unique for the lifetime of the process
+    pub fn generate() -> Self {
+        Self::new()
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    pub(crate) fn from_id(id: u64) -> Self {
+        Self::from(id)
+    }
+}
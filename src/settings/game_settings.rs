@@ -1,13 +1,24 @@
+use crate::core::Game;
+use crate::events::GameEvent;
+use crate::log;
+use crate::settings::setting_overrides::{self, SettingOverride};
+use crate::settings::setting_schema::{self, SettingError};
+use crate::settings::settings_file::{self, SettingsError};
 use crate::settings::Setting::*;
+use crate::settings::{SettingChangeRequestEvent, SettingChangedEvent};
 use crate::utils::logger::Severity;
 use crate::utils::logger::Severity::*;
 use crate::utils::types::*;
 #[cfg(feature = "window")]
 use crate::window::VsyncMode;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::RwLock;
+use std::{env, fs, io, sync};
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum Setting {
     Empty,
     Boolean(bool),
@@ -219,104 +230,725 @@ impl Setting {
     }
 }
 
+/// The type of a [`Setting`], as written in settings files
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SettingKind {
+    Empty,
+    Boolean,
+    Str,
+    UnsignedInt,
+    SignedInt,
+    FloatingPoint,
+    IVector2,
+    FVector2,
+    IVector3,
+    FVector3,
+    IVector4,
+    FVector4,
+    LogSeverity,
+    Vsync,
+}
+
+static KIND_NAMES: [(SettingKind, &str); 14] = [
+    (SettingKind::Empty, "empty"),
+    (SettingKind::Boolean, "bool"),
+    (SettingKind::Str, "string"),
+    (SettingKind::UnsignedInt, "uint"),
+    (SettingKind::SignedInt, "int"),
+    (SettingKind::FloatingPoint, "float"),
+    (SettingKind::IVector2, "ivec2"),
+    (SettingKind::FVector2, "fvec2"),
+    (SettingKind::IVector3, "ivec3"),
+    (SettingKind::FVector3, "fvec3"),
+    (SettingKind::IVector4, "ivec4"),
+    (SettingKind::FVector4, "fvec4"),
+    (SettingKind::LogSeverity, "severity"),
+    (SettingKind::Vsync, "vsync"),
+];
+
+impl SettingKind {
+    pub fn get_name(&self) -> &'static str {
+        KIND_NAMES.iter().find(|x| x.0 == *self).unwrap().1
+    }
+
+    /// Finds a kind from the name used in settings files
+    pub fn from_name(name: &str) -> Option<SettingKind> {
+        KIND_NAMES.iter().find(|x| x.1 == name).map(|x| x.0)
+    }
+}
+
+impl Display for SettingKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+impl Setting {
+    pub fn get_kind(&self) -> SettingKind {
+        match self {
+            Empty => SettingKind::Empty,
+            Boolean(_) => SettingKind::Boolean,
+            Str(_) => SettingKind::Str,
+            UnsignedInt(_) => SettingKind::UnsignedInt,
+            SignedInt(_) => SettingKind::SignedInt,
+            FloatingPoint(_) => SettingKind::FloatingPoint,
+            IVector2(_) => SettingKind::IVector2,
+            FVector2(_) => SettingKind::FVector2,
+            IVector3(_) => SettingKind::IVector3,
+            FVector3(_) => SettingKind::FVector3,
+            IVector4(_) => SettingKind::IVector4,
+            FVector4(_) => SettingKind::FVector4,
+            LogSeverity(_) => SettingKind::LogSeverity,
+            #[cfg(feature = "window")]
+            Vsync(_) => SettingKind::Vsync,
+        }
+    }
+
+    /// Parses a value of the given kind, written the way [`Display`] writes it.
+    ///
+    /// Strings may be quoted, and vector components are separated by commas,
+    /// or by `x` as in `800x600`
+    ///
+    /// # Arguments
+    /// * `kind` - The kind of the value
+    /// * `text` - The value
+    ///
+    /// # Returns
+    /// * The setting, or a description of what is wrong with the value
+    pub fn parse(kind: SettingKind, text: &str) -> Result<Setting, String> {
+        let text = text.trim();
+        Ok(match kind {
+            SettingKind::Empty => {
+                if !text.is_empty() {
+                    return Err(format!("Expected no value, found \"{}\"", text));
+                }
+                Empty
+            }
+            SettingKind::Boolean => Boolean(parse_value(text, "a boolean")?),
+            SettingKind::Str => Str(parse_string(text)?),
+            SettingKind::UnsignedInt => UnsignedInt(parse_value(text, "an unsigned integer")?),
+            SettingKind::SignedInt => SignedInt(parse_value(text, "an integer")?),
+            SettingKind::FloatingPoint => FloatingPoint(parse_value(text, "a number")?),
+            SettingKind::IVector2 => {
+                let [x, y] = parse_components(text, "an integer")?;
+                IVector2(Vector2i::new(x, y))
+            }
+            SettingKind::FVector2 => {
+                let [x, y] = parse_components(text, "a number")?;
+                FVector2(Vector2f::new(x, y))
+            }
+            SettingKind::IVector3 => {
+                let [x, y, z] = parse_components(text, "an integer")?;
+                IVector3(Vector3i::new(x, y, z))
+            }
+            SettingKind::FVector3 => {
+                let [x, y, z] = parse_components(text, "a number")?;
+                FVector3(Vector3f::new(x, y, z))
+            }
+            SettingKind::IVector4 => {
+                let [x, y, z, w] = parse_components(text, "an integer")?;
+                IVector4(Vector4i::new(x, y, z, w))
+            }
+            SettingKind::FVector4 => {
+                let [x, y, z, w] = parse_components(text, "a number")?;
+                FVector4(Vector4f::new(x, y, z, w))
+            }
+            SettingKind::LogSeverity => LogSeverity(
+                [Unknown, Debug, Info, Loading, Warning, Error, Fatal]
+                    .into_iter()
+                    .find(|x| x.to_string().eq_ignore_ascii_case(text))
+                    .ok_or_else(|| format!("\"{}\" is not a severity", text))?,
+            ),
+            #[cfg(feature = "window")]
+            SettingKind::Vsync => Vsync(match text.to_ascii_lowercase().as_str() {
+                "disabled" => VsyncMode::Disabled,
+                "enabled" => VsyncMode::Enabled,
+                "adaptive" => VsyncMode::Adaptive,
+                _ => return Err(format!("\"{}\" is not a vsync mode", text)),
+            }),
+            #[cfg(not(feature = "window"))]
+            SettingKind::Vsync => return Err(String::from("Vsync requires the window feature")),
+        })
+    }
+}
+
+fn parse_value<T: FromStr>(text: &str, expected: &str) -> Result<T, String> {
+    text.parse()
+        .map_err(|_| format!("\"{}\" is not {}", text, expected))
+}
+
+fn parse_components<T: FromStr + Copy + Default, const N: usize>(
+    text: &str,
+    expected: &str,
+) -> Result<[T; N], String> {
+    // Sizes can also be written as 800x600
+    let separator = if text.contains(',') { ',' } else { 'x' };
+    let parts: Vec<&str> = text.split(separator).map(|x| x.trim()).collect();
+    if parts.len() != N {
+        return Err(format!("Expected {} components, found {}", N, parts.len()));
+    }
+
+    let mut components = [T::default(); N];
+    for (component, part) in components.iter_mut().zip(parts) {
+        *component = parse_value(part, expected)?;
+    }
+    Ok(components)
+}
+
+fn parse_string(text: &str) -> Result<String, String> {
+    let inner = match text.strip_prefix('"') {
+        Some(inner) => match inner.strip_suffix('"') {
+            Some(inner) => inner,
+            None => return Err(String::from("Missing closing quote")),
+        },
+        // Unquoted strings are taken as they are
+        None => return Ok(text.to_string()),
+    };
+
+    let mut result = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('"') => result.push('"'),
+            Some('\\') => result.push('\\'),
+            Some(c) => return Err(format!("Unknown escape sequence \\{}", c)),
+            None => return Err(String::from("Unfinished escape sequence")),
+        }
+    }
+    Ok(result)
+}
+
+impl Display for Setting {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Empty => Ok(()),
+            Boolean(value) => write!(f, "{}", value),
+            Str(value) => {
+                write!(f, "\"")?;
+                for c in value.chars() {
+                    match c {
+                        '\n' => write!(f, "\\n")?,
+                        '\t' => write!(f, "\\t")?,
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            UnsignedInt(value) => write!(f, "{}", value),
+            SignedInt(value) => write!(f, "{}", value),
+            FloatingPoint(value) => write!(f, "{}", value),
+            IVector2(value) => write!(f, "{}, {}", value.x, value.y),
+            FVector2(value) => write!(f, "{}, {}", value.x, value.y),
+            IVector3(value) => write!(f, "{}, {}, {}", value.x, value.y, value.z),
+            FVector3(value) => write!(f, "{}, {}, {}", value.x, value.y, value.z),
+            IVector4(value) => write!(f, "{}, {}, {}, {}", value.x, value.y, value.z, value.w),
+            FVector4(value) => write!(f, "{}, {}, {}, {}", value.x, value.y, value.z, value.w),
+            LogSeverity(value) => write!(f, "{}", value),
+            #[cfg(feature = "window")]
+            Vsync(value) => write!(
+                f,
+                "{}",
+                match value {
+                    VsyncMode::Disabled => "Disabled",
+                    VsyncMode::Enabled => "Enabled",
+                    VsyncMode::Adaptive => "Adaptive",
+                }
+            ),
+        }
+    }
+}
+
+/// Where the value of a setting comes from. Each layer overrides the ones below it
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum SettingLayer {
+    /// The default values declared in the schema of each setting
+    EngineDefaults,
+    /// Values chosen by the game
+    GameDefaults,
+    /// Values loaded from, and saved to, the settings file of the user,
+    /// and changed through [`GameSettings::set_user`]
+    UserConfig,
+    /// Values given on the command line, or in environment variables
+    CommandLine,
+    /// Values changed while the game runs, through [`GameSettings::set`], which aren't saved
+    Runtime,
+}
+
+impl SettingLayer {
+    /// Every layer, from the bottom to the top
+    pub const ALL: [SettingLayer; 5] = [
+        SettingLayer::EngineDefaults,
+        SettingLayer::GameDefaults,
+        SettingLayer::UserConfig,
+        SettingLayer::CommandLine,
+        SettingLayer::Runtime,
+    ];
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
 pub struct GameSettings {
-    settings: RwLockHashMap<String, Setting>,
+    // One map for each layer, from the bottom to the top
+    layers: RwLock<Vec<HashMap<String, Setting>>>,
+    // Values of a kind this build can't represent, kept as written in the user config
+    preserved: RwLockHashMap<String, (String, String)>,
+    game: RwLock<sync::Weak<Game>>,
 }
 
 impl GameSettings {
     pub fn new() -> Self {
         let obj = Self {
-            settings: RwLock::new(HashMap::new()),
+            layers: RwLock::new(SettingLayer::ALL.iter().map(|_| HashMap::new()).collect()),
+            preserved: RwLock::new(HashMap::new()),
+            game: RwLock::new(sync::Weak::new()),
         };
 
-        obj.set("render.openAL", Boolean(true));
-        obj.set("render.openGL", Boolean(true));
-        obj.set("render.resolution", IVector2(Vector2i::new(1920, 1080))); // Render target resolution
-        obj.set("handler.stopTimeout", UnsignedInt(10000)); // 10 s
-        obj.set("assets.assetSheet", Str(String::from("/res/main.txt")));
-        obj.set(
-            "assets.internalSheet",
-            Str(String::from("/internal/internal_assets.txt")),
-        );
-        obj.set("engine.useCurrentThreadAsPrimary", Boolean(false));
+        // Start from the default value of every declared setting
+        setting_schema::with_schemas(|setting_name, schema| {
+            obj.insert(
+                SettingLayer::EngineDefaults,
+                setting_name,
+                schema.get_default().clone(),
+            )
+        });
+
+        obj
+    }
 
-        // Game window
-        #[cfg(feature = "window")]
+    pub(crate) fn link_game(&self, game: sync::Weak<Game>) {
+        *self.game.write().unwrap() = game;
+    }
+
+    /// Changes a setting in the [`SettingLayer::Runtime`] layer, which isn't saved.
+    /// Same as [`GameSettings::set_in`]
+    pub fn set(&self, setting_name: &str, value: Setting) -> Result<SettingLayer, SettingError> {
+        self.set_in(SettingLayer::Runtime, setting_name, value)
+    }
+
+    /// Changes a setting in the [`SettingLayer::UserConfig`] layer, so that it is saved.
+    /// Same as [`GameSettings::set_in`]
+    pub fn set_user(
+        &self,
+        setting_name: &str,
+        value: Setting,
+    ) -> Result<SettingLayer, SettingError> {
+        self.set_in(SettingLayer::UserConfig, setting_name, value)
+    }
+
+    /// Changes a setting in the given layer.
+    ///
+    /// The value is checked against the schema of the setting, if it is declared.
+    /// While the game owning the settings is running, and the change isn't hidden
+    /// by a layer above, a [`SettingChangeRequestEvent`] is raised first,
+    /// and its listeners can cancel the change or rewrite the new value.
+    /// Once the change is applied, a [`SettingChangedEvent`] is raised
+    ///
+    /// # Arguments
+    /// * `layer` - The layer to change the setting in
+    /// * `setting_name` - The name of the setting
+    /// * `value` - The new value
+    ///
+    /// # Returns
+    /// * The layer the value of the setting comes from after the change, which is above
+    /// `layer` if the change is hidden, or why the change wasn't applied
+    pub fn set_in(
+        &self,
+        layer: SettingLayer,
+        setting_name: &str,
+        value: Setting,
+    ) -> Result<SettingLayer, SettingError> {
+        let change = self.request_change(layer, setting_name, value)?;
+        Ok(self.apply_change(change))
+    }
+
+    // Checks a change against the schema, then asks the listeners of the running game for it
+    fn request_change(
+        &self,
+        layer: SettingLayer,
+        setting_name: &str,
+        value: Setting,
+    ) -> Result<PendingChange, SettingError> {
+        setting_schema::validate(setting_name, &value)?;
+
+        let mut change = PendingChange {
+            layer,
+            setting_name: setting_name.to_string(),
+            value,
+            hidden_by: self.get_layer_of(setting_name).filter(|x| *x > layer),
+            changed: None,
+        };
+
+        // The value is kept, and takes effect once the layers above are reset
+        if change.hidden_by.is_some() {
+            return Ok(change);
+        }
+        let game = match self.get_running_game() {
+            Some(game) => game,
+            None => return Ok(change),
+        };
+
+        let old_value = self.get(setting_name);
+        let request =
+            game.get_event_dispatcher()
+                .raise_typed_event_and_wait(SettingChangeRequestEvent::new(
+                    setting_name.to_string(),
+                    old_value.clone(),
+                    change.value,
+                ));
+        if request.get_event_data().is_cancelled() {
+            return Err(SettingError::Vetoed(setting_name.to_string()));
+        }
+
+        // Listeners may have rewritten the value
+        change.value = request.get_new_value().clone();
+        setting_schema::validate(setting_name, &change.value)?;
+
+        change.changed = Some((game, old_value));
+        Ok(change)
+    }
+
+    fn apply_change(&self, change: PendingChange) -> SettingLayer {
+        self.insert(change.layer, &change.setting_name, change.value.clone());
+        if let Some((game, old_value)) = change.changed {
+            Self::raise_changed(&game, &change.setting_name, old_value, change.value);
+        }
+        change.hidden_by.unwrap_or(change.layer)
+    }
+
+    /// Removes a setting from the topmost layer defining it,
+    /// so that it takes the value of the layer below.
+    /// Engine defaults can't be removed.
+    ///
+    /// While the game owning the settings is running, a [`SettingChangedEvent`]
+    /// is raised if the value changes
+    ///
+    /// # Arguments
+    /// * `setting_name` - The name of the setting
+    ///
+    /// # Returns
+    /// * The layer the setting was removed from, if any
+    pub fn reset(&self, setting_name: &str) -> Option<SettingLayer> {
+        let old_value = self.get(setting_name);
+        let mut removed = None;
         {
-            obj.set(
-                "window.fullscreenResolution",
-                IVector2(Vector2i::new(1920, 1080)),
-            ); // Preferred fullscreen resolution
-            obj.set("window.fullscreenMonitor", SignedInt(0));
-            obj.set("window.size", IVector2(Vector2i::new(256, 256)));
-            obj.set("window.minimumSize", IVector2(Vector2i::new(256, 256)));
-            obj.set("window.maximumSize", IVector2(Vector2i::new(-1, -1))); // No max size
-            obj.set("window.fullscreen", Boolean(false));
-            obj.set("window.resizable", Boolean(true));
-            obj.set("window.maximized", Boolean(false));
-            obj.set("window.vsync", Vsync(VsyncMode::Enabled));
-            obj.set("window.transparent", Boolean(false));
-
-            obj.set("window.debugContext", Boolean(true));
-
-            obj.set("window.title", Str(String::from("Spaghetti game")));
-            obj.set("window.icon16", Str(String::from("res/icon16.png")));
-            obj.set("window.icon32", Str(String::from("res/icon32.png")));
-        }
-
-        // Networking
-        obj.set("online.port", UnsignedInt(9018));
-        obj.set("online.bufferSize", UnsignedInt(1024 * 256)); // 256 KB
-        obj.set("online.timeoutTime", UnsignedInt(500000));
-        obj.set("online.verifyToken", Boolean(false));
-        obj.set("online.maxClients", UnsignedInt(10));
-        obj.set("online.maxDisconnections", UnsignedInt(10));
-        obj.set("online.awaitTimeout", UnsignedInt(10000));
-        obj.set("online.reconnectAttempts", UnsignedInt(10));
-
-        // Logging
-        obj.set("log.autoCreate", Boolean(true));
-
-        obj.set(
-            "log.printSeverity",
-            if cfg!(debug_assertions) {
-                LogSeverity(Debug)
-            } else {
-                LogSeverity(Info)
-            },
-        );
+            let mut layers = self.layers.write().unwrap();
+            let mut preserved = self.preserved.write().unwrap();
+
+            // Engine defaults stay
+            for layer in SettingLayer::ALL[1..].iter().rev() {
+                let mut found = layers[layer.index()].remove(setting_name).is_some();
+                if *layer == SettingLayer::UserConfig {
+                    found |= preserved.remove(setting_name).is_some();
+                }
+                if found {
+                    removed = Some(*layer);
+                    break;
+                }
+            }
+        }
 
-        obj.set("log.fileSeverity", LogSeverity(Debug));
+        let new_value = self.get(setting_name);
+        if let Some(game) = self.get_running_game() {
+            if removed.is_some() && new_value != old_value {
+                Self::raise_changed(&game, setting_name, old_value, new_value);
+            }
+        }
+        removed
+    }
 
-        obj
+    pub(crate) fn get_game(&self) -> Option<sync::Arc<Game>> {
+        self.game.read().unwrap().upgrade()
     }
 
-    pub fn set(&self, setting_name: &str, value: Setting) {
-        // TODO EVENT DISPATCHER
-        let mut map = self.settings.write().unwrap();
-        map.insert(setting_name.to_string(), value);
+    fn get_running_game(&self) -> Option<sync::Arc<Game>> {
+        self.get_game()
+            .filter(|game| game.is_running() && !game.get_event_dispatcher().is_shut_down())
     }
 
+    fn raise_changed(game: &Game, setting_name: &str, old_value: Setting, new_value: Setting) {
+        if setting_schema::get_schema(setting_name).is_some_and(|x| x.requires_restart()) {
+            log!(
+                Info,
+                "Setting {} changed, the game must be restarted for it to take effect",
+                setting_name
+            );
+        }
+
+        game.get_event_dispatcher().raise_event(
+            Box::new(SettingChangedEvent::new(
+                setting_name.to_string(),
+                old_value,
+                new_value,
+            )),
+            false,
+        );
+    }
+
+    fn insert(&self, layer: SettingLayer, setting_name: &str, value: Setting) {
+        let mut layers = self.layers.write().unwrap();
+        layers[layer.index()].insert(setting_name.to_string(), value);
+    }
+
+    /// Retrieves the value of a setting from the topmost layer defining it
+    ///
+    /// # Arguments
+    /// * `setting_name` - The name of the setting
+    ///
+    /// # Returns
+    /// * The value, or [`Setting::Empty`] if no layer defines it
     pub fn get(&self, setting_name: &str) -> Setting {
-        let map = self.settings.read().unwrap();
-        let option = map.get(setting_name);
-        if let Some(setting) = option {
+        let layers = self.layers.read().unwrap();
+        if let Some(setting) = layers.iter().rev().find_map(|x| x.get(setting_name)) {
             return setting.clone();
         }
-        Empty
+
+        // Settings declared after these settings were created
+        match setting_schema::get_schema(setting_name) {
+            Some(schema) => schema.get_default().clone(),
+            None => Empty,
+        }
+    }
+
+    /// Retrieves the value of a setting in the given layer only
+    ///
+    /// # Arguments
+    /// * `layer` - The layer
+    /// * `setting_name` - The name of the setting
+    ///
+    /// # Returns
+    /// * The value, or [`Setting::Empty`] if the layer doesn't define it
+    pub fn get_in(&self, layer: SettingLayer, setting_name: &str) -> Setting {
+        let layers = self.layers.read().unwrap();
+        match layers[layer.index()].get(setting_name) {
+            Some(setting) => setting.clone(),
+            None => Empty,
+        }
+    }
+
+    /// Finds the layer the value of a setting comes from
+    ///
+    /// # Arguments
+    /// * `setting_name` - The name of the setting
+    ///
+    /// # Returns
+    /// * The topmost layer defining the setting, if any
+    pub fn get_layer_of(&self, setting_name: &str) -> Option<SettingLayer> {
+        let layers = self.layers.read().unwrap();
+        let layer = SettingLayer::ALL
+            .iter()
+            .rev()
+            .find(|x| layers[x.index()].contains_key(setting_name))
+            .copied();
+        if layer.is_none() && setting_schema::get_schema(setting_name).is_some() {
+            return Some(SettingLayer::EngineDefaults);
+        }
+        layer
+    }
+
+    /// Reads settings from a file written by [`GameSettings::save`] into the
+    /// [`SettingLayer::UserConfig`] layer, on top of the current ones. Nothing is changed
+    /// if the file can't be read or parsed, or any value doesn't fit the schema of its setting
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    pub fn load<P: AsRef<Path>>(&self, path: P) -> Result<(), SettingsError> {
+        self.load_from_str(&fs::read_to_string(path)?)
+    }
+
+    /// Same as [`GameSettings::load`], reading the contents of a file
+    pub fn load_from_str(&self, text: &str) -> Result<(), SettingsError> {
+        let entries = settings_file::parse(text)?;
+        for entry in entries.iter() {
+            if let Ok(value) = entry.value.as_ref() {
+                setting_schema::validate(&entry.key, value).map_err(|error| {
+                    SettingsError::Parse {
+                        line: entry.line,
+                        message: error.to_string(),
+                    }
+                })?;
+            }
+        }
+
+        let mut layers = self.layers.write().unwrap();
+        let map = &mut layers[SettingLayer::UserConfig.index()];
+        let mut preserved = self.preserved.write().unwrap();
+        for entry in entries {
+            match entry.value {
+                Ok(value) => {
+                    preserved.remove(&entry.key);
+                    map.insert(entry.key, value);
+                }
+                Err(raw) => {
+                    map.remove(&entry.key);
+                    preserved.insert(entry.key, (entry.kind.get_name().to_string(), raw));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the settings of the [`SettingLayer::UserConfig`] layer to a file,
+    /// grouped in sections by the first part of their name. Settings this build can't
+    /// represent, but were loaded from a file, are written back as they were.
+    /// Declared settings are preceded by comments describing them
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.save_to_string())
+    }
+
+    /// Same as [`GameSettings::save`], returning the contents of the file
+    pub fn save_to_string(&self) -> String {
+        let layers = self.layers.read().unwrap();
+        let map = &layers[SettingLayer::UserConfig.index()];
+        let preserved = self.preserved.read().unwrap();
+
+        // Declared settings are described by their schema
+        let comments = |key: &str| {
+            setting_schema::get_schema(key)
+                .map(|x| x.get_comments())
+                .unwrap_or_default()
+        };
+        let mut lines: Vec<(&str, Vec<String>, String)> = map
+            .iter()
+            .map(|(key, value)| {
+                (
+                    key.as_str(),
+                    comments(key),
+                    format!("{} = {}", value.get_kind(), value),
+                )
+            })
+            .collect();
+        lines.extend(preserved.iter().map(|(key, (kind, raw))| {
+            (key.as_str(), comments(key), format!("{} = {}", kind, raw))
+        }));
+        settings_file::format(lines)
+    }
+
+    /// Applies the `--set name=value` arguments of the command line, such as
+    /// `--set online.port=9100 --set window.size=800x600`, to the
+    /// [`SettingLayer::CommandLine`] layer. Nothing is changed if any of them is invalid,
+    /// or vetoed by a listener
+    ///
+    /// # Arguments
+    /// * `args` - The command line arguments, without the name of the program
+    ///
+    /// # Returns
+    /// * The arguments not meant for settings, or an error
+    pub fn apply_args<I, S>(&self, args: I) -> Result<Vec<String>, SettingsError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let (overrides, remaining) =
+            setting_overrides::parse_args(args.into_iter().map(|x| x.into()).collect(), |key| {
+                self.get_kind_of(key)
+            })?;
+        self.apply_overrides(overrides)?;
+        Ok(remaining)
+    }
+
+    /// Applies the environment variables named after settings, such as
+    /// `SPAGHETTI_ONLINE_PORT` for `online.port`, to the [`SettingLayer::CommandLine`]
+    /// layer. Nothing is changed if any of them is invalid, or vetoed by a listener
+    ///
+    /// # Arguments
+    /// * `vars` - The environment variables, as names and values
+    pub fn apply_env_vars<I>(&self, vars: I) -> Result<(), SettingsError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut keys: Vec<String> = self
+            .layers
+            .read()
+            .unwrap()
+            .iter()
+            .flat_map(|x| x.keys().cloned())
+            .collect();
+        setting_schema::with_schemas(|key, _| keys.push(key.to_string()));
+        keys.sort();
+        keys.dedup();
+
+        let overrides =
+            setting_overrides::parse_env_vars(vars.into_iter().collect(), &keys, |key| {
+                self.get_kind_of(key)
+            })?;
+        self.apply_overrides(overrides)
+    }
+
+    /// Applies the environment variables of the process, then its command line arguments,
+    /// so that arguments take precedence
+    ///
+    /// # Returns
+    /// * The arguments not meant for settings, or an error
+    pub fn apply_process_overrides(&self) -> Result<Vec<String>, SettingsError> {
+        self.apply_env_vars(setting_overrides::unicode_vars(env::vars_os()))?;
+        self.apply_args(env::args().skip(1))
+    }
+
+    // The declared kind of a setting, or the kind of its current value
+    fn get_kind_of(&self, setting_name: &str) -> Option<SettingKind> {
+        match setting_schema::get_schema(setting_name) {
+            Some(schema) => Some(schema.get_kind()),
+            None => Some(self.get(setting_name).get_kind()).filter(|x| *x != SettingKind::Empty),
+        }
+    }
+
+    fn apply_overrides(&self, overrides: Vec<SettingOverride>) -> Result<(), SettingsError> {
+        let invalid =
+            |entry: &SettingOverride, error: SettingError| SettingsError::InvalidOverride {
+                source: entry.source.clone(),
+                message: error.to_string(),
+            };
+
+        for entry in overrides.iter() {
+            setting_schema::validate(&entry.key, &entry.value).map_err(|x| invalid(entry, x))?;
+        }
+
+        // Every change is requested before any is applied, so that a veto changes nothing
+        let mut changes = Vec::new();
+        for entry in overrides.iter() {
+            changes.push(
+                self.request_change(SettingLayer::CommandLine, &entry.key, entry.value.clone())
+                    .map_err(|x| invalid(entry, x))?,
+            );
+        }
+        for change in changes {
+            self.apply_change(change);
+        }
+        Ok(())
     }
 }
 
+// A change allowed by the listeners, not applied yet
+struct PendingChange {
+    layer: SettingLayer,
+    setting_name: String,
+    value: Setting,
+    // The layer above hiding the change, if any
+    hidden_by: Option<SettingLayer>,
+    // The running game to raise a SettingChangedEvent in, with the old value
+    changed: Option<(sync::Arc<Game>, Setting)>,
+}
+
 impl Clone for GameSettings {
     fn clone(&self) -> Self {
         let settings = Self::new();
         {
-            // Lock settings
-            let mut other_map = settings.settings.write().unwrap();
-            let our_map = self.settings.read().unwrap();
-
             // Copy settings
-            other_map.extend(our_map.iter().map(|(k, v)| (k.clone(), v.clone())));
+            *settings.layers.write().unwrap() = self.layers.read().unwrap().clone();
+            *settings.preserved.write().unwrap() = self.preserved.read().unwrap().clone();
         }
         settings
     }
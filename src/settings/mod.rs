@@ -1,8 +1,16 @@
 pub mod game_settings;
 pub mod setting_change_request_event;
 pub mod setting_changed_event;
+pub mod setting_overrides;
+pub mod setting_schema;
+pub mod settings_file;
 
 pub use game_settings::GameSettings;
 pub use game_settings::Setting;
+pub use game_settings::SettingKind;
+pub use game_settings::SettingLayer;
 pub use setting_change_request_event::SettingChangeRequestEvent;
 pub use setting_changed_event::SettingChangedEvent;
+pub use setting_schema::SettingError;
+pub use setting_schema::SettingSchema;
+pub use settings_file::SettingsError;
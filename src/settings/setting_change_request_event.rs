@@ -11,7 +11,12 @@ pub struct SettingChangeRequestEvent {
     new_value: Setting,
 }
 
-register_game_event!(SettingChangeRequestEvent, data -> event_data, new -> new_empty);
+register_game_event!(
+    SettingChangeRequestEvent,
+    data -> event_data,
+    new -> new_empty,
+    replicate -> [setting_name, old_value, new_value]
+);
 
 impl SettingChangeRequestEvent {
     pub fn new_empty() -> Box<dyn GameEvent> {
@@ -11,7 +11,12 @@ pub struct SettingChangedEvent {
     new_value: Setting,
 }
 
-register_game_event!(SettingChangedEvent, data -> event_data, new -> new_empty);
+register_game_event!(
+    SettingChangedEvent,
+    data -> event_data,
+    new -> new_empty,
+    replicate -> [setting_name, old_value, new_value]
+);
 
 impl SettingChangedEvent {
     pub fn new_empty() -> Box<dyn GameEvent> {
@@ -0,0 +1,171 @@
+use crate::log;
+use crate::settings::game_settings::SettingKind;
+use crate::settings::settings_file::SettingsError;
+use crate::settings::Setting;
+use std::ffi::OsString;
+
+/// Prefix of the environment variables overriding settings
+pub static ENV_PREFIX: &str = "SPAGHETTI_";
+
+/// A setting overridden from outside the game
+pub(crate) struct SettingOverride {
+    pub key: String,
+    pub value: Setting,
+    /// Where the override comes from, for errors
+    pub source: String,
+}
+
+/// Finds every `--set name=value` (or `--set=name=value`) argument
+///
+/// # Arguments
+/// * `args` - The command line arguments, without the name of the program
+/// * `kind_of` - Finds the kind of a setting from its name
+///
+/// # Returns
+/// * The overrides and the remaining arguments, or an error if any override is invalid
+pub(crate) fn parse_args<F>(
+    args: Vec<String>,
+    kind_of: F,
+) -> Result<(Vec<SettingOverride>, Vec<String>), SettingsError>
+where
+    F: Fn(&str) -> Option<SettingKind>,
+{
+    let mut overrides = Vec::new();
+    let mut remaining = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let text = if arg == "--set" {
+            match args.next() {
+                Some(text) => text,
+                None => return Err(invalid("--set", "Expected name=value after --set")),
+            }
+        } else if let Some(text) = arg.strip_prefix("--set=") {
+            text.to_string()
+        } else {
+            remaining.push(arg);
+            continue;
+        };
+
+        let source = format!("--set {}", text);
+        let (key, value) = match text.split_once('=') {
+            Some(split) => split,
+            None => return Err(invalid(&source, "Expected name=value")),
+        };
+        let key = key.trim();
+        let kind = match kind_of(key) {
+            Some(kind) => kind,
+            None => return Err(invalid(&source, &format!("Unknown setting {}", key))),
+        };
+        overrides.push(SettingOverride {
+            key: key.to_string(),
+            value: Setting::parse(kind, value).map_err(|message| invalid(&source, &message))?,
+            source,
+        });
+    }
+    Ok((overrides, remaining))
+}
+
+/// Finds every environment variable named after a setting, such as
+/// `SPAGHETTI_ONLINE_PORT` for `online.port`
+///
+/// # Arguments
+/// * `vars` - The environment variables
+/// * `keys` - The names of the settings that can be overridden
+/// * `kind_of` - Finds the kind of a setting from its name
+///
+/// # Returns
+/// * The overrides, or an error if any override is invalid
+pub(crate) fn parse_env_vars<F>(
+    vars: Vec<(String, String)>,
+    keys: &[String],
+    kind_of: F,
+) -> Result<Vec<SettingOverride>, SettingsError>
+where
+    F: Fn(&str) -> Option<SettingKind>,
+{
+    let mut overrides = Vec::new();
+    for (name, value) in vars {
+        if !name.starts_with(ENV_PREFIX) {
+            continue;
+        }
+
+        // Other variables with the prefix may not be meant for settings
+        let key = match keys.iter().find(|key| env_var_name(key) == name) {
+            Some(key) => key,
+            None => continue,
+        };
+        let kind = match kind_of(key) {
+            Some(kind) => kind,
+            None => continue,
+        };
+        overrides.push(SettingOverride {
+            key: key.clone(),
+            value: Setting::parse(kind, &value).map_err(|message| invalid(&name, &message))?,
+            source: name,
+        });
+    }
+    Ok(overrides)
+}
+
+/// Keeps the environment variables that are valid unicode, the others can't override
+/// settings. Those that look like they were meant to are logged
+///
+/// # Arguments
+/// * `vars` - The environment variables, as returned by [`std::env::vars_os`]
+///
+/// # Returns
+/// * The variables that are valid unicode
+pub(crate) fn unicode_vars<I>(vars: I) -> Vec<(String, String)>
+where
+    I: IntoIterator<Item = (OsString, OsString)>,
+{
+    let mut unicode = Vec::new();
+    for (name, value) in vars {
+        match (name.to_str(), value.to_str()) {
+            (Some(name), Some(value)) => unicode.push((name.to_string(), value.to_string())),
+            _ => {
+                let name = name.to_string_lossy();
+                if name.starts_with(ENV_PREFIX) {
+                    log!(
+                        Warning,
+                        "Ignoring environment variable {}, it isn't valid unicode",
+                        name
+                    );
+                }
+            }
+        }
+    }
+    unicode
+}
+
+/// Retrieves the name of the environment variable overriding a setting
+///
+/// # Arguments
+/// * `setting_name` - The name of the setting, such as `window.fullscreenMonitor`
+///
+/// # Returns
+/// * The name of the variable, such as `SPAGHETTI_WINDOW_FULLSCREEN_MONITOR`
+pub fn env_var_name(setting_name: &str) -> String {
+    let mut name = String::from(ENV_PREFIX);
+    let mut previous_lowercase = false;
+    for c in setting_name.chars() {
+        if c == '.' {
+            name.push('_');
+        } else {
+            if c.is_ascii_uppercase() && previous_lowercase {
+                name.push('_');
+            }
+            name.push(c.to_ascii_uppercase());
+        }
+        previous_lowercase = c.is_ascii_lowercase() || c.is_ascii_digit();
+    }
+    name
+}
+
+fn invalid(source: &str, message: &str) -> SettingsError {
+    SettingsError::InvalidOverride {
+        source: source.to_string(),
+        message: message.to_string(),
+    }
+}
@@ -0,0 +1,536 @@
+use crate::settings::game_settings::SettingKind;
+use crate::settings::settings_file;
+use crate::settings::Setting;
+use crate::settings::Setting::*;
+use crate::utils::logger::Severity::*;
+use crate::utils::types::*;
+#[cfg(feature = "window")]
+use crate::window::VsyncMode;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::RwLock;
+
+/// Why a setting couldn't be changed
+#[derive(Debug)]
+pub enum SettingError {
+    WrongKind {
+        setting_name: String,
+        expected: SettingKind,
+        found: SettingKind,
+    },
+    OutOfRange {
+        setting_name: String,
+        min: f64,
+        max: f64,
+    },
+    NotAllowed(String),
+    /// A listener cancelled the change
+    Vetoed(String),
+    AlreadyDeclared(String),
+}
+
+impl Error for SettingError {}
+
+impl Display for SettingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingError::WrongKind {
+                setting_name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Setting {} expects a value of kind {}, found {}",
+                setting_name, expected, found
+            ),
+            SettingError::OutOfRange {
+                setting_name,
+                min,
+                max,
+            } => write!(
+                f,
+                "Setting {} must be between {} and {}",
+                setting_name, min, max
+            ),
+            SettingError::NotAllowed(setting_name) => {
+                write!(f, "Value not allowed for setting {}", setting_name)
+            }
+            SettingError::Vetoed(setting_name) => {
+                write!(f, "Change to setting {} was cancelled", setting_name)
+            }
+            SettingError::AlreadyDeclared(setting_name) => {
+                write!(f, "Setting {} is already declared", setting_name)
+            }
+        }
+    }
+}
+
+/// Describes a setting: the kind of its values, its default value, which values are
+/// allowed and whether changing it requires restarting the game to take effect
+#[derive(Clone)]
+pub struct SettingSchema {
+    default: Setting,
+    description: String,
+    range: Option<(f64, f64)>,
+    allowed: Option<Vec<Setting>>,
+    optional: bool,
+    requires_restart: bool,
+}
+
+impl SettingSchema {
+    /// Describes a setting of the same kind as its default value
+    ///
+    /// # Arguments
+    /// * `default` - The default value
+    /// * `description` - What the setting does
+    pub fn new(default: Setting, description: &str) -> Self {
+        Self {
+            default,
+            description: description.to_string(),
+            range: None,
+            allowed: None,
+            optional: false,
+            requires_restart: false,
+        }
+    }
+
+    /// Restricts numbers, or each component of vectors, to the given range
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Restricts values to the given ones
+    pub fn with_allowed(mut self, allowed: Vec<Setting>) -> Self {
+        self.allowed = Some(allowed);
+        self
+    }
+
+    /// Also allows [`Setting::Empty`], meaning the setting is not used
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    /// Changes to the setting only take effect once the game is restarted
+    pub fn requiring_restart(mut self) -> Self {
+        self.requires_restart = true;
+        self
+    }
+
+    pub fn get_kind(&self) -> SettingKind {
+        self.default.get_kind()
+    }
+
+    pub fn get_default(&self) -> &Setting {
+        &self.default
+    }
+
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn get_range(&self) -> Option<(f64, f64)> {
+        self.range
+    }
+
+    pub fn get_allowed(&self) -> Option<&Vec<Setting>> {
+        self.allowed.as_ref()
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+
+    pub fn requires_restart(&self) -> bool {
+        self.requires_restart
+    }
+
+    /// Describes the setting in lines of text, as written above it in settings files
+    pub(crate) fn get_comments(&self) -> Vec<String> {
+        let mut comments = vec![self.description.clone()];
+        if let Some((min, max)) = self.range {
+            comments.push(format!("Range: {} to {}", min, max));
+        }
+        if let Some(allowed) = self.allowed.as_ref() {
+            let allowed: Vec<String> = allowed.iter().map(|x| x.to_string()).collect();
+            comments.push(format!("Allowed: {}", allowed.join(", ")));
+        }
+        if self.optional {
+            comments.push(String::from("Optional"));
+        }
+        if self.requires_restart {
+            comments.push(String::from("Requires a restart"));
+        }
+        comments
+    }
+
+    /// Checks that a value fits the schema
+    ///
+    /// # Arguments
+    /// * `setting_name` - The name of the setting, for the error
+    /// * `value` - The value
+    ///
+    /// # Returns
+    /// * Nothing, or the reason the value doesn't fit
+    pub fn validate(&self, setting_name: &str, value: &Setting) -> Result<(), SettingError> {
+        if value.is_empty() && self.optional {
+            return Ok(());
+        }
+
+        if value.get_kind() != self.get_kind() {
+            return Err(SettingError::WrongKind {
+                setting_name: setting_name.to_string(),
+                expected: self.get_kind(),
+                found: value.get_kind(),
+            });
+        }
+
+        if let Some((min, max)) = self.range {
+            if numeric_components(value)
+                .iter()
+                .any(|x| *x < min || *x > max)
+            {
+                return Err(SettingError::OutOfRange {
+                    setting_name: setting_name.to_string(),
+                    min,
+                    max,
+                });
+            }
+        }
+
+        if let Some(allowed) = self.allowed.as_ref() {
+            if !allowed.contains(value) {
+                return Err(SettingError::NotAllowed(setting_name.to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn numeric_components(value: &Setting) -> Vec<f64> {
+    match value {
+        UnsignedInt(value) => vec![*value as f64],
+        SignedInt(value) => vec![*value as f64],
+        FloatingPoint(value) => vec![*value],
+        IVector2(value) => vec![value.x as f64, value.y as f64],
+        FVector2(value) => vec![value.x as f64, value.y as f64],
+        IVector3(value) => vec![value.x as f64, value.y as f64, value.z as f64],
+        FVector3(value) => vec![value.x as f64, value.y as f64, value.z as f64],
+        IVector4(value) => vec![
+            value.x as f64,
+            value.y as f64,
+            value.z as f64,
+            value.w as f64,
+        ],
+        FVector4(value) => vec![
+            value.x as f64,
+            value.y as f64,
+            value.z as f64,
+            value.w as f64,
+        ],
+        _ => Vec::new(),
+    }
+}
+
+static SCHEMAS: Lazy<RwLock<HashMap<String, SettingSchema>>> =
+    Lazy::new(|| RwLock::new(engine_schemas()));
+
+fn engine_schemas() -> HashMap<String, SettingSchema> {
+    let mut map = HashMap::new();
+    let mut declare = |name: &str, schema: SettingSchema| {
+        map.insert(name.to_string(), schema);
+    };
+
+    declare(
+        "render.openAL",
+        SettingSchema::new(Boolean(true), "Enables audio through OpenAL").requiring_restart(),
+    );
+    declare(
+        "render.openGL",
+        SettingSchema::new(Boolean(true), "Enables rendering through OpenGL").requiring_restart(),
+    );
+    declare(
+        "render.resolution",
+        SettingSchema::new(
+            IVector2(Vector2i::new(1920, 1080)),
+            "Resolution of the render target",
+        )
+        .with_range(1.0, 16384.0),
+    );
+    declare(
+        "handler.stopTimeout",
+        SettingSchema::new(
+            UnsignedInt(10000),
+            "Milliseconds to wait for components to stop",
+        ),
+    );
+    declare(
+        "assets.assetSheet",
+        SettingSchema::new(
+            Str(String::from("/res/main.txt")),
+            "Path of the sheet listing the assets of the game",
+        )
+        .requiring_restart(),
+    );
+    declare(
+        "assets.internalSheet",
+        SettingSchema::new(
+            Str(String::from("/internal/internal_assets.txt")),
+            "Path of the sheet listing the assets of the engine",
+        )
+        .requiring_restart(),
+    );
+    declare(
+        "engine.useCurrentThreadAsPrimary",
+        SettingSchema::new(
+            Boolean(false),
+            "Runs the main loop on the thread starting the engine",
+        )
+        .requiring_restart(),
+    );
+    declare(
+        "engine.tickRate",
+        SettingSchema::new(UnsignedInt(60), "Fixed simulation steps per second")
+            .with_range(1.0, 1000.0)
+            .requiring_restart(),
+    );
+    declare(
+        "engine.maxCatchUpTicks",
+        SettingSchema::new(
+            UnsignedInt(5),
+            "Simulation steps run at most per cycle to catch up",
+        )
+        .with_range(1.0, 1000.0)
+        .requiring_restart(),
+    );
+
+    // Game window
+    #[cfg(feature = "window")]
+    {
+        declare(
+            "window.fullscreenResolution",
+            SettingSchema::new(
+                IVector2(Vector2i::new(1920, 1080)),
+                "Preferred fullscreen resolution",
+            )
+            .with_range(1.0, 16384.0),
+        );
+        declare(
+            "window.fullscreenMonitor",
+            SettingSchema::new(SignedInt(0), "Index of the monitor used in fullscreen")
+                .with_range(0.0, 64.0),
+        );
+        declare(
+            "window.size",
+            SettingSchema::new(
+                IVector2(Vector2i::new(256, 256)),
+                "Size of the window in screen coordinates",
+            )
+            .with_range(1.0, 16384.0),
+        );
+        declare(
+            "window.minimumSize",
+            SettingSchema::new(
+                IVector2(Vector2i::new(256, 256)),
+                "Minimum size of the window, negative for no limit",
+            ),
+        );
+        declare(
+            "window.maximumSize",
+            SettingSchema::new(
+                IVector2(Vector2i::new(-1, -1)),
+                "Maximum size of the window, negative for no limit",
+            ),
+        );
+        declare(
+            "window.fullscreen",
+            SettingSchema::new(Boolean(false), "Starts the window in fullscreen"),
+        );
+        declare(
+            "window.resizable",
+            SettingSchema::new(Boolean(true), "Allows resizing the window"),
+        );
+        declare(
+            "window.maximized",
+            SettingSchema::new(Boolean(false), "Maximizes the window"),
+        );
+        declare(
+            "window.vsync",
+            SettingSchema::new(
+                Vsync(VsyncMode::Enabled),
+                "Synchronizes frames with the monitor",
+            ),
+        );
+        declare(
+            "window.transparent",
+            SettingSchema::new(Boolean(false), "Makes the framebuffer transparent")
+                .requiring_restart(),
+        );
+        declare(
+            "window.debugContext",
+            SettingSchema::new(Boolean(true), "Creates an OpenGL debug context")
+                .requiring_restart(),
+        );
+        declare(
+            "window.title",
+            SettingSchema::new(Str(String::from("Spaghetti game")), "Title of the window"),
+        );
+        declare(
+            "window.icon16",
+            SettingSchema::new(
+                Str(String::from("res/icon16.png")),
+                "Path of the 16x16 window icon",
+            )
+            .optional()
+            .requiring_restart(),
+        );
+        declare(
+            "window.icon32",
+            SettingSchema::new(
+                Str(String::from("res/icon32.png")),
+                "Path of the 32x32 window icon",
+            )
+            .optional()
+            .requiring_restart(),
+        );
+    }
+
+    // Networking
+    declare(
+        "online.port",
+        SettingSchema::new(UnsignedInt(9018), "Port the server listens on")
+            .with_range(1.0, 65535.0)
+            .requiring_restart(),
+    );
+    declare(
+        "online.bufferSize",
+        SettingSchema::new(UnsignedInt(1024 * 256), "Size in bytes of network buffers")
+            .with_range(1024.0, (1024 * 1024 * 64) as f64)
+            .requiring_restart(),
+    );
+    declare(
+        "online.timeoutTime",
+        SettingSchema::new(
+            UnsignedInt(500000),
+            "Milliseconds without data before a connection times out",
+        ),
+    );
+    declare(
+        "online.verifyToken",
+        SettingSchema::new(Boolean(false), "Verifies the tokens of connecting clients"),
+    );
+    declare(
+        "online.maxClients",
+        SettingSchema::new(UnsignedInt(10), "Clients the server accepts at most")
+            .with_range(1.0, 65535.0),
+    );
+    declare(
+        "online.maxDisconnections",
+        SettingSchema::new(
+            UnsignedInt(10),
+            "Disconnections tolerated before giving up on a client",
+        ),
+    );
+    declare(
+        "online.awaitTimeout",
+        SettingSchema::new(
+            UnsignedInt(10000),
+            "Milliseconds to wait for a response from the other peer",
+        ),
+    );
+    declare(
+        "online.reconnectAttempts",
+        SettingSchema::new(UnsignedInt(10), "Attempts to reconnect to the server"),
+    );
+
+    // Logging
+    declare(
+        "log.autoCreate",
+        SettingSchema::new(Boolean(true), "Creates a log file when needed"),
+    );
+    declare(
+        "log.printSeverity",
+        SettingSchema::new(
+            LogSeverity(if cfg!(debug_assertions) { Debug } else { Info }),
+            "Minimum severity of messages printed to standard output",
+        ),
+    );
+    declare(
+        "log.fileSeverity",
+        SettingSchema::new(
+            LogSeverity(Debug),
+            "Minimum severity of messages written to the log file",
+        ),
+    );
+
+    map
+}
+
+/// Declares a setting, so that its values are validated
+/// and it has a default value in every [`GameSettings`](crate::settings::GameSettings)
+///
+/// # Arguments
+/// * `setting_name` - The name of the setting
+/// * `schema` - The description of the setting
+///
+/// # Returns
+/// * Nothing, or an error if the setting is already declared
+pub fn declare_setting(setting_name: &str, schema: SettingSchema) -> Result<(), SettingError> {
+    let mut schemas = SCHEMAS.write().unwrap();
+    if schemas.contains_key(setting_name) {
+        return Err(SettingError::AlreadyDeclared(setting_name.to_string()));
+    }
+    schemas.insert(setting_name.to_string(), schema);
+    Ok(())
+}
+
+pub fn get_schema(setting_name: &str) -> Option<SettingSchema> {
+    SCHEMAS.read().unwrap().get(setting_name).cloned()
+}
+
+/// Calls `f` on every declared setting, in order of name
+pub fn with_schemas<T>(mut f: T)
+where
+    T: FnMut(&str, &SettingSchema),
+{
+    let schemas = SCHEMAS.read().unwrap();
+    let mut sorted: Vec<(&String, &SettingSchema)> = schemas.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, schema) in sorted {
+        f(name, schema);
+    }
+}
+
+/// Checks a value against the schema of a setting. Undeclared settings accept any value
+///
+/// # Returns
+/// * Nothing, or the reason the value doesn't fit
+pub fn validate(setting_name: &str, value: &Setting) -> Result<(), SettingError> {
+    match SCHEMAS.read().unwrap().get(setting_name) {
+        Some(schema) => schema.validate(setting_name, value),
+        None => Ok(()),
+    }
+}
+
+/// Writes every declared setting, with its default value, as a settings file
+/// documenting each setting in comments
+pub fn dump() -> String {
+    let mut lines = Vec::new();
+    with_schemas(|name, schema| {
+        let comments = schema.get_comments();
+        lines.push((
+            name.to_string(),
+            comments,
+            format!("{} = {}", schema.get_kind(), schema.default),
+        ));
+    });
+    settings_file::format(
+        lines
+            .iter()
+            .map(|(name, comments, line)| (name.as_str(), comments.clone(), line.clone()))
+            .collect(),
+    )
+}
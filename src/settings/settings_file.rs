@@ -0,0 +1,155 @@
+use crate::settings::game_settings::SettingKind;
+use crate::settings::Setting;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::io;
+
+/// Why settings couldn't be loaded
+#[derive(Debug)]
+pub enum SettingsError {
+    IOError(io::Error),
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// A setting overridden from the command line or the environment
+    InvalidOverride {
+        source: String,
+        message: String,
+    },
+}
+
+impl Error for SettingsError {}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsError::IOError(error) => write!(f, "IOError: {}", error),
+            SettingsError::Parse { line, message } => write!(f, "Line {}: {}", line, message),
+            SettingsError::InvalidOverride { source, message } => {
+                write!(f, "{}: {}", source, message)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for SettingsError {
+    fn from(error: io::Error) -> Self {
+        SettingsError::IOError(error)
+    }
+}
+
+/// A setting read from a settings file
+pub(crate) struct SettingEntry {
+    pub line: usize,
+    pub key: String,
+    pub kind: SettingKind,
+    /// The value, or its text if this build can't represent its kind
+    pub value: Result<Setting, String>,
+}
+
+/// Parses the contents of a settings file.
+///
+/// Each line is either empty, a `# comment`, a `[section]` header or a
+/// `name: kind = value` setting. Settings belong to the last section
+/// header before them, which is prepended to their name
+pub(crate) fn parse(text: &str) -> Result<Vec<SettingEntry>, SettingsError> {
+    let mut entries = Vec::new();
+    let mut section = String::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let error = |message: String| SettingsError::Parse {
+            line: index + 1,
+            message,
+        };
+
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            match header.strip_suffix(']') {
+                Some(header) => section = header.trim().to_string(),
+                None => return Err(error(String::from("Missing closing bracket"))),
+            }
+            continue;
+        }
+
+        let (name, rest) = match line.split_once(':') {
+            Some(split) => split,
+            None => return Err(error(String::from("Expected \"name: kind = value\""))),
+        };
+        let (kind, value) = match rest.split_once('=') {
+            Some(split) => split,
+            None => return Err(error(String::from("Expected \"name: kind = value\""))),
+        };
+
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(error(String::from("Missing setting name")));
+        }
+        let kind = match SettingKind::from_name(kind.trim()) {
+            Some(kind) => kind,
+            None => return Err(error(format!("Unknown setting kind \"{}\"", kind.trim()))),
+        };
+
+        let value = if kind == SettingKind::Vsync && cfg!(not(feature = "window")) {
+            Err(value.trim().to_string())
+        } else {
+            Ok(Setting::parse(kind, value).map_err(error)?)
+        };
+
+        entries.push(SettingEntry {
+            line: index + 1,
+            key: if section.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", section, name)
+            },
+            kind,
+            value,
+        });
+    }
+    Ok(entries)
+}
+
+/// Writes the contents of a settings file, sorting settings in sections
+///
+/// # Arguments
+/// * `lines` - The name of each setting, the comments written above it and its `kind = value` text
+pub(crate) fn format(lines: Vec<(&str, Vec<String>, String)>) -> String {
+    let mut sections: BTreeMap<&str, Vec<(&str, Vec<String>, String)>> = BTreeMap::new();
+    for (key, comments, line) in lines {
+        let (section, name) = key.split_once('.').unwrap_or(("", key));
+        sections
+            .entry(section)
+            .or_default()
+            .push((name, comments, line));
+    }
+
+    // Settings without a section come first, the empty name sorts first
+    let mut text = String::new();
+    for (section, mut lines) in sections {
+        if !section.is_empty() {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&format!("[{}]\n", section));
+        }
+
+        lines.sort_by(|a, b| a.0.cmp(b.0));
+        for (index, (name, comments, line)) in lines.into_iter().enumerate() {
+            // Keep commented settings apart from each other
+            if index > 0 && !comments.is_empty() {
+                text.push('\n');
+            }
+            for comment in comments {
+                text.push_str(&format!("# {}\n", comment));
+            }
+            text.push_str(&format!("{}: {}\n", name, line));
+        }
+    }
+    text
+}
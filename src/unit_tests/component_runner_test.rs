@@ -0,0 +1,147 @@
+use crate::core::{ComponentRunner, Game, ThreadComponent};
+use crate::settings::Setting::*;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{sync, thread};
+
+struct RecordingComponent {
+    name: &'static str,
+    record: Arc<Mutex<Vec<String>>>,
+}
+
+impl RecordingComponent {
+    fn push(&self, hook: &str) {
+        self.record
+            .lock()
+            .unwrap()
+            .push(format!("{}:{}", self.name, hook));
+    }
+}
+
+impl ThreadComponent for RecordingComponent {
+    fn initialize(&mut self) {
+        self.push("initialize");
+    }
+
+    fn post_initialize(&mut self) {
+        self.push("post_initialize");
+    }
+
+    fn loop_cycle(&mut self, delta: f32) {
+        assert!(delta >= 0.0);
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    fn pre_terminate(&mut self) {
+        self.push("pre_terminate");
+    }
+
+    fn terminate(&mut self) {
+        self.push("terminate");
+    }
+}
+
+struct FailingComponent {
+    panic_in_loop: bool,
+}
+
+impl ThreadComponent for FailingComponent {
+    fn initialize(&mut self) {}
+
+    fn post_initialize(&mut self) {}
+
+    fn loop_cycle(&mut self, _delta: f32) {
+        if self.panic_in_loop {
+            panic!("Component failure");
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    fn pre_terminate(&mut self) {}
+
+    fn terminate(&mut self) {
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+#[test]
+fn component_runner_phases() {
+    let record = Arc::new(Mutex::new(Vec::new()));
+    let mut runner = ComponentRunner::new(sync::Weak::new());
+    for name in ["first", "second", "third"] {
+        runner.add_component(
+            name,
+            Box::new(RecordingComponent {
+                name,
+                record: record.clone(),
+            }),
+        );
+    }
+
+    assert!(runner.start());
+    thread::sleep(Duration::from_millis(10));
+    assert!(runner.stop().is_empty());
+
+    let record = record.lock().unwrap();
+    let position = |entry: &str| record.iter().position(|x| x == entry).unwrap();
+
+    // Lockstep initialization
+    for name in ["first", "second", "third"] {
+        for other in ["first", "second", "third"] {
+            assert!(
+                position(&format!("{}:initialize", name))
+                    < position(&format!("{}:post_initialize", other))
+            );
+        }
+    }
+
+    // Reverse termination
+    assert_eq!(
+        record[6..],
+        [
+            "third:pre_terminate",
+            "second:pre_terminate",
+            "first:pre_terminate",
+            "third:terminate",
+            "second:terminate",
+            "first:terminate",
+        ]
+    );
+}
+
+#[test]
+fn component_runner_failures() {
+    let game = Game::builder()
+        .with_name("runner_failures")
+        .as_server()
+        .headless()
+        .build();
+    game.get_settings()
+        .set("handler.stopTimeout", UnsignedInt(50))
+        .unwrap();
+
+    let mut runner = ComponentRunner::new(Arc::downgrade(&game));
+    runner.add_component(
+        "panicking",
+        Box::new(FailingComponent {
+            panic_in_loop: true,
+        }),
+    );
+    runner.add_component(
+        "hanging",
+        Box::new(FailingComponent {
+            panic_in_loop: false,
+        }),
+    );
+    assert!(runner.start());
+
+    // A panicking component stops the game
+    while !game.is_stop_requested() {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    // Components that take too long to terminate are abandoned
+    let stopping = Instant::now();
+    assert_eq!(runner.stop(), vec!["panicking", "hanging"]);
+    assert!(stopping.elapsed() < Duration::from_millis(500));
+}
@@ -0,0 +1,57 @@
+use crate::core::coroutine::Coroutines;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn coroutine_waits() {
+    let mut coroutines = Coroutines::new();
+    let progress = Arc::new(AtomicU32::new(0));
+    let flag = Arc::new(AtomicBool::new(false));
+
+    let counter = progress.clone();
+    let condition = flag.clone();
+    coroutines.start(|ctx| async move {
+        counter.store(1, Ordering::SeqCst);
+        ctx.wait_ticks(2).await;
+        counter.store(2, Ordering::SeqCst);
+        ctx.wait(Duration::from_secs(1)).await;
+        counter.store(3, Ordering::SeqCst);
+        ctx.wait_until(move || condition.load(Ordering::SeqCst)).await;
+        counter.store(4, Ordering::SeqCst);
+    });
+
+    let tick = Duration::from_millis(500);
+    let mut resume = |expected: u32| {
+        coroutines.resume(tick);
+        assert_eq!(progress.load(Ordering::SeqCst), expected);
+    };
+    resume(1);
+    resume(1);
+    resume(2);
+    resume(2);
+    resume(3);
+    resume(3);
+
+    flag.store(true, Ordering::SeqCst);
+    resume(4);
+    assert!(coroutines.is_empty());
+}
+
+#[test]
+fn coroutine_cancel() {
+    let mut coroutines = Coroutines::new();
+    let finished = Arc::new(AtomicBool::new(false));
+
+    let flag = finished.clone();
+    let handle = coroutines.start(|ctx| async move {
+        ctx.next_tick().await;
+        flag.store(true, Ordering::SeqCst);
+    });
+
+    coroutines.resume(Duration::ZERO);
+    assert!(coroutines.cancel(handle));
+    coroutines.resume(Duration::ZERO);
+    assert!(!finished.load(Ordering::SeqCst));
+    assert!(!coroutines.is_running(handle));
+}
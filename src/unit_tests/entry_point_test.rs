@@ -0,0 +1,84 @@
+use crate::core::entry_point::{
+    clear_failed_tasks, get_failed_tasks, is_main_thread, register_task, send_event,
+    send_event_async, unregister_task,
+};
+use crate::spaghetti_debug_entry_point;
+use std::panic;
+use std::sync::{mpsc, Mutex};
+
+// The entry point is global, tests using it can't run in parallel
+pub(super) static ENTRY_POINT: Mutex<()> = Mutex::new(());
+
+#[test]
+fn entry_point_events() {
+    let _lock = ENTRY_POINT.lock().unwrap();
+    spaghetti_debug_entry_point!(|| {
+        // Blocking call
+        let value = send_event(|| {
+            assert!(is_main_thread());
+            21 * 2
+        });
+        assert_eq!(value, 42);
+
+        // Non blocking call
+        let result = send_event_async(|| String::from("done"));
+        assert_eq!(result.wait(), "done");
+
+        // Panics reach the caller
+        let caught = panic::catch_unwind(|| {
+            send_event(|| {
+                panic!("main thread panic");
+            })
+        });
+        assert!(caught.is_err());
+
+        // The main thread is still alive
+        assert!(send_event(|| true));
+    });
+}
+
+#[test]
+fn entry_point_task_panics() {
+    let _lock = ENTRY_POINT.lock().unwrap();
+    spaghetti_debug_entry_point!(|| {
+        // The follow-up event runs in the next loop, once the panic is recorded
+        let (sender, receiver) = mpsc::channel();
+        let handle = register_task(move || {
+            sender.send(send_event_async(|| {})).unwrap();
+            panic!("task panic");
+        });
+        receiver.recv().unwrap().wait();
+
+        let failures = get_failed_tasks();
+        let failure = failures.iter().find(|x| x.handle == handle).unwrap();
+        assert!(failure.unregistered);
+        assert_eq!(failure.message, "task panic");
+        clear_failed_tasks();
+    });
+}
+
+#[test]
+fn entry_point_task_registration() {
+    let _lock = ENTRY_POINT.lock().unwrap();
+    spaghetti_debug_entry_point!(|| {
+        // Tasks can register and unregister tasks
+        let doomed = register_task(|| {});
+        let (sender, receiver) = mpsc::channel();
+        let outer = register_task(move || {
+            unregister_task(doomed);
+            let _ = sender.send(register_task(|| {}));
+        });
+        let inner = receiver.recv().unwrap();
+        unregister_task(outer);
+        unregister_task(inner);
+    });
+}
+
+#[test]
+fn entry_point_not_running() {
+    let _lock = ENTRY_POINT.lock().unwrap();
+
+    // Nothing would ever run the closures, they are discarded right away
+    assert!(send_event_async(|| {}).is_done());
+    assert!(panic::catch_unwind(|| send_event(|| {})).is_err());
+}
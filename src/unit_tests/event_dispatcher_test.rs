@@ -0,0 +1,315 @@
+use crate::events::event_listener::LambdaEL;
+use crate::events::{
+    EventDispatcher, GameEvent, ListenerOptions, ListenerPriority, NothingHappenedEvent,
+    TypedEventListener,
+};
+use crate::settings::SettingChangedEvent;
+use crate::utils::PanicPolicy;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn event_dispatcher_listener_panics() {
+    let dispatcher = EventDispatcher::new(false);
+    let calls = Arc::new(AtomicU32::new(0));
+
+    let counter = calls.clone();
+    dispatcher
+        .register_event_listener::<NothingHappenedEvent>(Box::new(LambdaEL::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })))
+        .unwrap();
+    let panicking = dispatcher
+        .register_event_listener::<NothingHappenedEvent>(Box::new(LambdaEL::new(|_| {
+            panic!("listener panic");
+        })))
+        .unwrap();
+    dispatcher
+        .register_event_listener_with_policy::<NothingHappenedEvent>(
+            Box::new(LambdaEL::new(|_| panic!("kept listener panic"))),
+            PanicPolicy::Keep,
+        )
+        .unwrap();
+
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+    dispatcher.process_events();
+    assert!(calls.load(Ordering::SeqCst) >= 1);
+
+    let failures = dispatcher.get_failed_listeners();
+    assert_eq!(failures.len(), 2);
+    assert!(failures[0].handle == panicking);
+    assert!(failures[0].unregistered);
+    assert_eq!(failures[0].message, "listener panic");
+    assert!(!failures[1].unregistered);
+
+    // Only the kept listener can fail again
+    dispatcher.clear_failed_listeners();
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+    dispatcher.process_events();
+    let failures = dispatcher.get_failed_listeners();
+    assert!(failures.iter().all(|failure| !failure.unregistered));
+}
+
+#[test]
+fn event_dispatcher_queue() {
+    let dispatcher = Arc::new(EventDispatcher::new(false));
+    let calls = Arc::new(AtomicU32::new(0));
+
+    let counter = calls.clone();
+    let inner = Arc::downgrade(&dispatcher);
+    dispatcher
+        .register_event_listener::<NothingHappenedEvent>(Box::new(LambdaEL::new(move |_| {
+            // Events raised while dispatching wait for the next tick
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                let dispatcher = inner.upgrade().unwrap();
+                dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+            }
+        })))
+        .unwrap();
+
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+    dispatcher.process_events();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(dispatcher.get_pending_count(), 1);
+
+    dispatcher.process_events();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Events are dispatched only once
+    dispatcher.process_events();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn event_dispatcher_sync_raise() {
+    let dispatcher = Arc::new(EventDispatcher::new(false));
+    dispatcher
+        .register_event_listener::<NothingHappenedEvent>(Box::new(LambdaEL::new(|event| {
+            event.get_event_data_mut().set_cancelled(true);
+        })))
+        .unwrap();
+
+    // The raising thread gets the handled event back
+    let raiser = dispatcher.clone();
+    let thread = thread::spawn(move || {
+        let event = raiser.raise_event_and_wait(NothingHappenedEvent::new_empty());
+        event.get_event_data().is_cancelled()
+    });
+    while !thread.is_finished() {
+        dispatcher.process_events();
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert!(thread.join().unwrap());
+
+    // The processing thread dispatches its own sync events immediately
+    let event = NothingHappenedEvent::new_empty()
+        .downcast::<NothingHappenedEvent>()
+        .ok()
+        .unwrap();
+    let event = dispatcher.raise_typed_event_and_wait(event);
+    assert!(event.get_event_data().is_cancelled());
+    assert_eq!(dispatcher.get_pending_count(), 0);
+}
+
+#[test]
+fn event_dispatcher_priorities() {
+    let dispatcher = EventDispatcher::new(false);
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let register = |name: &'static str, options: ListenerOptions, cancel: bool| {
+        let order = order.clone();
+        dispatcher
+            .register_event_listener_with_options::<NothingHappenedEvent>(
+                Box::new(LambdaEL::new(move |event| {
+                    order.lock().unwrap().push(name);
+                    if cancel {
+                        event.get_event_data_mut().set_cancelled(true);
+                    }
+                })),
+                options,
+            )
+            .unwrap();
+    };
+    let options = ListenerOptions::new();
+    let monitor = options
+        .with_priority(ListenerPriority::Monitor)
+        .receiving_cancelled();
+    register("monitor", monitor, false);
+    register("normal 1", options, false);
+    register("high", options.with_priority(ListenerPriority::High), true);
+    register(
+        "lowest",
+        options.with_priority(ListenerPriority::Lowest),
+        false,
+    );
+    register("normal 2", options, false);
+    register(
+        "highest",
+        options.with_priority(ListenerPriority::Highest),
+        false,
+    );
+
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+    dispatcher.process_events();
+
+    // Cancelled by the high listener, only the monitor sees it afterwards
+    assert_eq!(
+        *order.lock().unwrap(),
+        vec!["lowest", "normal 1", "normal 2", "high", "monitor"]
+    );
+}
+
+struct CountingListener {
+    calls: Arc<AtomicU32>,
+}
+
+impl TypedEventListener<NothingHappenedEvent> for CountingListener {
+    fn handle_event(&self, event: &mut NothingHappenedEvent) {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        event.get_event_data_mut().set_cancelled(true);
+    }
+}
+
+#[test]
+fn event_dispatcher_typed_listeners() {
+    let dispatcher = EventDispatcher::new(false);
+    let calls = Arc::new(AtomicU32::new(0));
+
+    let counter = calls.clone();
+    dispatcher
+        .listen(move |event: &mut NothingHappenedEvent| {
+            assert!(!event.get_event_data().is_cancelled());
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    let listener = CountingListener {
+        calls: calls.clone(),
+    };
+    let options = ListenerOptions::new().with_priority(ListenerPriority::High);
+    dispatcher
+        .register_typed_listener(listener, options)
+        .unwrap();
+
+    let event = dispatcher.raise_event_and_wait(NothingHappenedEvent::new_empty());
+    assert!(event.get_event_data().is_cancelled());
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn event_dispatcher_scheduled_events() {
+    let dispatcher = EventDispatcher::new(false);
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    dispatcher
+        .listen(move |_: &mut NothingHappenedEvent| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+    let after = dispatcher.raise_after(NothingHappenedEvent::new_empty(), 2);
+    let at = dispatcher.raise_at(NothingHappenedEvent::new_empty(), 1);
+    let cancelled = dispatcher.raise_after(NothingHappenedEvent::new_empty(), 1);
+    let timed =
+        dispatcher.raise_after(NothingHappenedEvent::new_empty(), Duration::from_millis(20));
+    assert!(dispatcher.cancel_scheduled(cancelled));
+    assert!(!dispatcher.cancel_scheduled(cancelled));
+    assert_eq!(dispatcher.get_scheduled_count(), 3);
+
+    // Tick 0
+    dispatcher.process_events();
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    // Tick 1
+    dispatcher.process_events();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(!dispatcher.is_scheduled(at));
+    assert!(dispatcher.is_scheduled(after));
+
+    // Tick 2
+    dispatcher.process_events();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    thread::sleep(Duration::from_millis(30));
+    dispatcher.process_events();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert!(!dispatcher.is_scheduled(timed));
+
+    // Scheduled events never outlive the dispatcher
+    dispatcher.raise_after(NothingHappenedEvent::new_empty(), 1);
+    dispatcher.shutdown();
+    assert_eq!(dispatcher.get_scheduled_count(), 0);
+    let handle = dispatcher.raise_at(NothingHappenedEvent::new_empty(), 0);
+    assert!(!dispatcher.is_scheduled(handle));
+
+    // Neither do queued ones, and waiting for them doesn't block
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+    let event = thread::scope(|scope| {
+        scope
+            .spawn(|| dispatcher.raise_event_and_wait(NothingHappenedEvent::new_empty()))
+            .join()
+            .unwrap()
+    });
+    assert!(event.get_event_data().is_cancelled());
+    dispatcher.process_events();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn event_dispatcher_listener_lifetimes() {
+    let dispatcher = EventDispatcher::new(false);
+    let calls = Arc::new(AtomicU32::new(0));
+
+    // Guards unregister on drop, unless forgotten
+    let counter = calls.clone();
+    let guard = dispatcher
+        .listen_guarded(move |_: &mut NothingHappenedEvent| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    let handle = guard.get_handle();
+    assert!(dispatcher.is_listener_registered(handle));
+    drop(guard);
+    assert!(!dispatcher.is_listener_registered(handle));
+
+    let forgotten = dispatcher
+        .listen_guarded(|_: &mut NothingHappenedEvent| {})
+        .unwrap()
+        .forget();
+    assert!(dispatcher.is_listener_registered(forgotten));
+
+    // Once listeners receive a single event
+    let counter = calls.clone();
+    let once = dispatcher
+        .listen_once(move |_: &mut NothingHappenedEvent| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), false);
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), false);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(!dispatcher.is_listener_registered(once));
+
+    // Bound listeners go away with their owner
+    let owner = Arc::new(AtomicU32::new(0));
+    let bound = dispatcher
+        .listen_bound(&owner, |owner: &AtomicU32, _: &mut NothingHappenedEvent| {
+            owner.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), false);
+    assert_eq!(owner.load(Ordering::SeqCst), 1);
+    drop(owner);
+    dispatcher.process_events();
+    assert!(!dispatcher.is_listener_registered(bound));
+
+    // Registering any listener also prunes them
+    let owner = Arc::new(AtomicU32::new(0));
+    let bound = dispatcher
+        .listen_bound(&owner, |_: &AtomicU32, _: &mut NothingHappenedEvent| {})
+        .unwrap();
+    drop(owner);
+    dispatcher.listen(|_: &mut SettingChangedEvent| {}).unwrap();
+    assert!(!dispatcher.is_listener_registered(bound));
+}
@@ -0,0 +1,135 @@
+use crate::events::{
+    DebugTracer, EventDispatcher, EventInterceptor, EventSpy, GameEvent, InterceptAction,
+    InterceptContext, NothingHappenedEvent, RateLimiter,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+fn count_calls(dispatcher: &EventDispatcher) -> Arc<AtomicU32> {
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    dispatcher
+        .listen(move |_: &mut NothingHappenedEvent| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    calls
+}
+
+#[test]
+fn event_interceptor_chain() {
+    let dispatcher = EventDispatcher::new(false);
+    let calls = count_calls(&dispatcher);
+    let spy = EventSpy::new();
+    dispatcher.add_interceptor(Box::new(DebugTracer::new()));
+    dispatcher.add_interceptor(Box::new(spy.clone()));
+
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+    dispatcher.process_events();
+    assert_eq!(spy.count_of::<NothingHappenedEvent>(), 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Cancelled events don't reach listeners, but reach later interceptors
+    let cancel = dispatcher.add_interceptor(Box::new(
+        |_: &mut Box<dyn GameEvent>, _: &InterceptContext| InterceptAction::Cancel,
+    ));
+    let after = EventSpy::new();
+    dispatcher.add_interceptor(Box::new(after.clone()));
+    let event = dispatcher.raise_event_and_wait(NothingHappenedEvent::new_empty());
+    assert!(event.get_event_data().is_cancelled());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(after.get_events().len(), 1);
+    dispatcher.remove_interceptor(cancel);
+
+    // Delayed events go through the chain again once due
+    spy.clear();
+    let delayed = Arc::new(AtomicU32::new(0));
+    let counter = delayed.clone();
+    dispatcher.add_interceptor(Box::new(
+        move |_: &mut Box<dyn GameEvent>, _: &InterceptContext| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                InterceptAction::Delay(2)
+            } else {
+                InterceptAction::Continue
+            }
+        },
+    ));
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+    dispatcher.process_events();
+    assert_eq!(dispatcher.get_delayed_count(), 1);
+    dispatcher.process_events();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    dispatcher.process_events();
+    assert_eq!(dispatcher.get_delayed_count(), 0);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(spy.get_events().len(), 2);
+}
+
+#[test]
+fn event_interceptor_inline_delay() {
+    let dispatcher = EventDispatcher::new(false);
+    let calls = count_calls(&dispatcher);
+    dispatcher.add_interceptor(Box::new(
+        |_: &mut Box<dyn GameEvent>, _: &InterceptContext| InterceptAction::Delay(1),
+    ));
+    let spy = EventSpy::new();
+    dispatcher.add_interceptor(Box::new(spy.clone()));
+
+    // Events dispatched inline can't be delayed, the rest of the chain still sees them
+    dispatcher.raise_event_and_wait(NothingHappenedEvent::new_empty());
+    assert_eq!(spy.get_events().len(), 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(dispatcher.get_delayed_count(), 0);
+}
+
+#[test]
+fn event_interceptor_rate_limiter() {
+    let limiter = RateLimiter::new(2);
+    let dropping = RateLimiter::new(2).dropping_excess();
+    let mut event = NothingHappenedEvent::new_empty();
+    let local = InterceptContext {
+        tick: 0,
+        remote: false,
+    };
+    let remote = InterceptContext {
+        tick: 0,
+        remote: true,
+    };
+
+    // Local events are not limited
+    for _ in 0..5 {
+        assert_eq!(
+            limiter.intercept(&mut event, &local),
+            InterceptAction::Continue
+        );
+    }
+
+    for _ in 0..2 {
+        assert_eq!(
+            limiter.intercept(&mut event, &remote),
+            InterceptAction::Continue
+        );
+        assert_eq!(
+            dropping.intercept(&mut event, &remote),
+            InterceptAction::Continue
+        );
+    }
+    assert_eq!(
+        limiter.intercept(&mut event, &remote),
+        InterceptAction::Delay(1)
+    );
+    assert_eq!(
+        dropping.intercept(&mut event, &remote),
+        InterceptAction::Cancel
+    );
+
+    // The limit resets every tick
+    let next = InterceptContext {
+        tick: 1,
+        remote: true,
+    };
+    assert_eq!(
+        limiter.intercept(&mut event, &next),
+        InterceptAction::Continue
+    );
+}
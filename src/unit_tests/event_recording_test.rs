@@ -0,0 +1,123 @@
+use crate::events::event_listener::LambdaEL;
+use crate::events::game_event::EventId;
+use crate::events::GameEvent;
+use crate::events::{
+    EventDispatcher, EventRecorder, EventReplayer, EventSource, NothingHappenedEvent, RateLimiter,
+    ReplayError,
+};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Collects the recording in memory
+#[derive(Clone)]
+struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn collect_ids(dispatcher: &EventDispatcher) -> Arc<Mutex<Vec<EventId>>> {
+    let ids = Arc::new(Mutex::new(Vec::new()));
+    let list = ids.clone();
+    dispatcher
+        .listen(move |event: &mut NothingHappenedEvent| {
+            let data = event.get_event_data();
+            assert!(data.get_from() == EventSource::Server);
+            list.lock().unwrap().push(data.get_id().clone());
+        })
+        .unwrap();
+    ids
+}
+
+#[test]
+fn event_recording_replay() {
+    let _lock = super::event_registry_test::REGISTRY.lock().unwrap();
+
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let dispatcher = EventDispatcher::new(false);
+    let ids = collect_ids(&dispatcher);
+
+    // Ticks before the recording starts are not part of it
+    dispatcher.process_events();
+    dispatcher
+        .start_recording(EventRecorder::new(Box::new(SharedWriter(recorded.clone()))).unwrap());
+    assert!(dispatcher.is_recording());
+
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+    dispatcher.process_events();
+    dispatcher.process_events();
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+    dispatcher.process_events();
+    dispatcher.stop_recording().unwrap().finish().unwrap();
+    assert!(!dispatcher.is_recording());
+
+    // Replay into a fresh dispatcher, at the same ticks.
+    // Replayed events aren't limited like the ones received from other peers
+    let replaying = EventDispatcher::new(true);
+    let replayed = collect_ids(&replaying);
+    replaying.add_interceptor(Box::new(RateLimiter::new(1).dropping_excess()));
+    let data = recorded.lock().unwrap().clone();
+    replaying.start_replay(EventReplayer::from_bytes(data).unwrap());
+    assert!(replaying.is_replaying());
+
+    let expected = ids.lock().unwrap().clone();
+    replaying.process_events();
+    assert_eq!(replayed.lock().unwrap().len(), 1);
+    replaying.process_events();
+    assert_eq!(replayed.lock().unwrap().len(), 1);
+    replaying.process_events();
+    assert!(!replaying.is_replaying());
+    assert!(*replayed.lock().unwrap() == expected);
+}
+
+#[test]
+fn event_recording_derived_events() {
+    let _lock = super::event_registry_test::REGISTRY.lock().unwrap();
+
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let dispatcher = Arc::new(EventDispatcher::new(false));
+    let inner = Arc::downgrade(&dispatcher);
+    let raised = AtomicBool::new(false);
+    dispatcher
+        .register_event_listener::<NothingHappenedEvent>(Box::new(LambdaEL::new(move |_| {
+            // The event raised by the listener reaches it too
+            if !raised.swap(true, Ordering::SeqCst) {
+                let dispatcher = inner.upgrade().unwrap();
+                dispatcher.raise_event(NothingHappenedEvent::new_empty(), false);
+            }
+        })))
+        .unwrap();
+
+    dispatcher
+        .start_recording(EventRecorder::new(Box::new(SharedWriter(recorded.clone()))).unwrap());
+    dispatcher.raise_event(NothingHappenedEvent::new_empty(), true);
+    dispatcher.process_events();
+    dispatcher.stop_recording().unwrap().finish().unwrap();
+
+    // Derived events are skipped unless asked for
+    let data = recorded.lock().unwrap().clone();
+    for (include_derived, count) in [(false, 1), (true, 2)] {
+        let replaying = EventDispatcher::new(false);
+        let replayed = collect_ids(&replaying);
+        let mut replayer = EventReplayer::from_bytes(data.clone()).unwrap();
+        if include_derived {
+            replayer = replayer.with_derived_events();
+        }
+        replaying.start_replay(replayer);
+        replaying.process_events();
+        assert_eq!(replayed.lock().unwrap().len(), count);
+    }
+
+    // Garbage is not a recording
+    let result = EventReplayer::from_bytes(b"garbage!garbage!".to_vec());
+    assert!(matches!(result, Err(ReplayError::InvalidHeader)));
+}
@@ -1,5 +1,12 @@
-use crate::events::event_registry;
+use crate::core::{Game, RunError};
+use crate::events::event_registry::{self, EventType, RegistrationError};
+use crate::events::{EventData, EventRoute, GameEvent, NothingHappenedEvent};
 use crate::log;
+use std::sync::Mutex;
+
+// Registering event types changes the registry fingerprint, and collisions prevent games
+// from running. Tests depending on either can't run in parallel with those registering types
+pub(super) static REGISTRY: Mutex<()> = Mutex::new(());
 
 #[test]
 fn event_registry() {
@@ -8,3 +15,118 @@ fn event_registry() {
         log!(Debug, "Event name: {}", data.name);
     })
 }
+
+struct FirstTestEvent {
+    event_data: EventData,
+}
+
+struct SecondTestEvent {
+    event_data: EventData,
+}
+
+impl GameEvent for FirstTestEvent {
+    fn get_event_data(&self) -> &EventData {
+        &self.event_data
+    }
+    fn get_event_data_mut(&mut self) -> &mut EventData {
+        &mut self.event_data
+    }
+    fn get_event_type(&self) -> EventType {
+        event_registry::get_event_type_of::<Self>().unwrap()
+    }
+}
+
+impl GameEvent for SecondTestEvent {
+    fn get_event_data(&self) -> &EventData {
+        &self.event_data
+    }
+    fn get_event_data_mut(&mut self) -> &mut EventData {
+        &mut self.event_data
+    }
+    fn get_event_type(&self) -> EventType {
+        event_registry::get_event_type_of::<Self>().unwrap()
+    }
+}
+
+fn new_first() -> Box<dyn GameEvent> {
+    Box::new(FirstTestEvent {
+        event_data: EventData::new(),
+    })
+}
+
+fn new_second() -> Box<dyn GameEvent> {
+    Box::new(SecondTestEvent {
+        event_data: EventData::new(),
+    })
+}
+
+#[test]
+fn event_registry_stable_ids() {
+    // Default ids only depend on the name of the type
+    let name = "spaghetti_engine::events::nothing_happened_event::NothingHappenedEvent";
+    let event_type = event_registry::get_event_type_of::<NothingHappenedEvent>().unwrap();
+    assert_eq!(
+        event_type.get_id(),
+        event_registry::event_type_id_of_name(name)
+    );
+    assert!(event_registry::get_event_type_by_name(name) == Some(event_type));
+    assert!(EventType::from_id(event_type.get_id()) == Some(event_type));
+
+    let _lock = REGISTRY.lock().unwrap();
+    let fingerprint = event_registry::registry_fingerprint();
+    let first = event_registry::register_event_type::<FirstTestEvent>(
+        "registry_test::first",
+        Some(0x5eed),
+        EventRoute::LocalOnly,
+        new_first,
+    )
+    .unwrap();
+    assert_eq!(first.get_id(), 0x5eed);
+    assert_ne!(fingerprint, event_registry::registry_fingerprint());
+
+    // Collisions are detected
+    let result = event_registry::register_event_type::<FirstTestEvent>(
+        "registry_test::other",
+        None,
+        EventRoute::LocalOnly,
+        new_first,
+    );
+    assert!(matches!(
+        result,
+        Err(RegistrationError::AlreadyRegistered(_))
+    ));
+    let result = event_registry::register_event_type::<SecondTestEvent>(
+        "registry_test::second",
+        Some(0x5eed),
+        EventRoute::LocalOnly,
+        new_second,
+    );
+    assert!(matches!(result, Err(RegistrationError::IdCollision { .. })));
+    let result = event_registry::register_event_type::<SecondTestEvent>(
+        "registry_test::first",
+        None,
+        EventRoute::LocalOnly,
+        new_second,
+    );
+    assert!(matches!(result, Err(RegistrationError::NameCollision(_))));
+
+    // Collisions while the program starts are reported afterwards
+    assert!(event_registry::check_registrations().is_ok());
+    event_registry::register_startup_event_type::<SecondTestEvent>(
+        "registry_test::first",
+        None,
+        EventRoute::LocalOnly,
+        new_second,
+    );
+    assert!(matches!(
+        event_registry::check_registrations(),
+        Err(RegistrationError::NameCollision(_))
+    ));
+    let game = Game::builder()
+        .with_name("registry_collision")
+        .as_server()
+        .headless()
+        .build();
+    assert!(matches!(game.run(), Err(RunError::InvalidEventRegistry(_))));
+    event_registry::clear_startup_errors();
+}
@@ -0,0 +1,208 @@
+use crate::events::event_registry;
+use crate::events::{
+    EventData, EventDispatcher, EventSource, EventTarget, EventTransport, GameEvent, RoutingError,
+};
+use crate::networking::token::Token;
+use crate::networking::NetworkBuffer;
+use crate::register_game_event;
+use std::sync::{Arc, Mutex, Weak};
+
+struct PingEvent {
+    event_data: EventData,
+    value: u32,
+}
+
+register_game_event!(PingEvent, data -> event_data, new -> new_empty, route -> ToServer, replicate -> [value]);
+
+impl PingEvent {
+    fn new(value: u32) -> Box<Self> {
+        Box::new(Self {
+            event_data: EventData::new(),
+            value,
+        })
+    }
+
+    fn new_empty() -> Box<dyn GameEvent> {
+        PingEvent::new(0)
+    }
+}
+
+struct BroadcastEvent {
+    event_data: EventData,
+}
+
+register_game_event!(BroadcastEvent, data -> event_data, new -> new_empty, route -> ToAllClients);
+
+impl BroadcastEvent {
+    fn new_empty() -> Box<dyn GameEvent> {
+        Box::new(Self {
+            event_data: EventData::new(),
+        })
+    }
+}
+
+struct WhisperEvent {
+    event_data: EventData,
+}
+
+register_game_event!(WhisperEvent, data -> event_data, new -> new_empty, route -> ToOwner);
+
+impl WhisperEvent {
+    fn new_empty() -> Box<dyn GameEvent> {
+        Box::new(Self {
+            event_data: EventData::new(),
+        })
+    }
+}
+
+// Delivers events straight to the other dispatchers
+struct ClientTransport {
+    token: Token,
+    server: Weak<EventDispatcher>,
+}
+
+impl EventTransport for ClientTransport {
+    fn send(&self, target: EventTarget, buffer: NetworkBuffer) {
+        assert!(target == EventTarget::Server);
+        let server = self.server.upgrade().unwrap();
+        let mut buffer = NetworkBuffer::from_bytes(buffer.into_bytes());
+        server
+            .receive_remote_event(&mut buffer, Some(self.token))
+            .unwrap();
+    }
+}
+
+struct ServerTransport {
+    clients: Vec<(Token, Weak<EventDispatcher>)>,
+}
+
+impl EventTransport for ServerTransport {
+    fn send(&self, target: EventTarget, buffer: NetworkBuffer) {
+        for (token, client) in self.clients.iter() {
+            if target == EventTarget::AllClients || target == EventTarget::Client(*token) {
+                let client = client.upgrade().unwrap();
+                let mut buffer = NetworkBuffer::from_bytes(buffer.as_bytes().to_vec());
+                client.receive_remote_event(&mut buffer, None).unwrap();
+            }
+        }
+    }
+}
+
+fn count<T: GameEvent>(dispatcher: &EventDispatcher) -> Arc<Mutex<u32>> {
+    let calls = Arc::new(Mutex::new(0));
+    let counter = calls.clone();
+    dispatcher
+        .listen(move |_: &mut T| *counter.lock().unwrap() += 1)
+        .unwrap();
+    calls
+}
+
+#[test]
+fn event_routing_loopback() {
+    let server = Arc::new(EventDispatcher::new(false));
+    let first = Arc::new(EventDispatcher::new(true));
+    let second = Arc::new(EventDispatcher::new(true));
+    let (first_token, second_token) = (Token::generate(), Token::generate());
+
+    server.set_transport(Some(Arc::new(ServerTransport {
+        clients: vec![
+            (first_token, Arc::downgrade(&first)),
+            (second_token, Arc::downgrade(&second)),
+        ],
+    })));
+    for (token, client) in [(first_token, &first), (second_token, &second)] {
+        client.set_transport(Some(Arc::new(ClientTransport {
+            token,
+            server: Arc::downgrade(&server),
+        })));
+    }
+
+    // Client to server, owned by the sender
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let list = received.clone();
+    server
+        .listen(move |event: &mut PingEvent| {
+            let data = event.get_event_data();
+            assert!(data.get_from() == EventSource::Client);
+            list.lock().unwrap().push((event.value, data.get_owner()));
+        })
+        .unwrap();
+    let local_pings = count::<PingEvent>(&first);
+    first.raise_event(PingEvent::new(7), true);
+    first.process_events();
+    assert_eq!(*local_pings.lock().unwrap(), 1);
+    server.process_events();
+    assert!(*received.lock().unwrap() == vec![(7, Some(first_token))]);
+
+    // Server to every client
+    let first_broadcasts = count::<BroadcastEvent>(&first);
+    let second_broadcasts = count::<BroadcastEvent>(&second);
+    server.raise_event(BroadcastEvent::new_empty(), true);
+    server.process_events();
+    first.process_events();
+    second.process_events();
+    assert_eq!(*first_broadcasts.lock().unwrap(), 1);
+    assert_eq!(*second_broadcasts.lock().unwrap(), 1);
+
+    // Server to the owner only
+    let first_whispers = count::<WhisperEvent>(&first);
+    let second_whispers = count::<WhisperEvent>(&second);
+    let mut whisper = WhisperEvent::new_empty();
+    whisper.get_event_data_mut().set_owner(Some(second_token));
+    server.raise_event(whisper, true);
+    server.process_events();
+    first.process_events();
+    second.process_events();
+    assert_eq!(*first_whispers.lock().unwrap(), 0);
+    assert_eq!(*second_whispers.lock().unwrap(), 1);
+
+    // Received events are not sent back
+    assert_eq!(server.get_pending_count(), 0);
+}
+
+#[test]
+fn event_routing_validation() {
+    let server = EventDispatcher::new(false);
+    let client = EventDispatcher::new(true);
+    let token = Token::generate();
+
+    // A client pretending to be the server
+    let mut ping = PingEvent::new(1);
+    ping.get_event_data_mut().set_from(EventSource::Server);
+    let mut buffer = NetworkBuffer::new();
+    event_registry::serialize_event(ping.as_ref(), &mut buffer);
+    let result = server.receive_remote_event(&mut buffer, Some(token));
+    assert!(matches!(result, Err(RoutingError::SpoofedSource(_))));
+
+    // Events routed to clients can't be sent by them
+    let mut broadcast = BroadcastEvent::new_empty();
+    broadcast.get_event_data_mut().set_from(EventSource::Client);
+    let mut buffer = NetworkBuffer::new();
+    event_registry::serialize_event(broadcast.as_ref(), &mut buffer);
+    let result = server.receive_remote_event(&mut buffer, Some(token));
+    assert!(matches!(result, Err(RoutingError::WrongDirection(_))));
+
+    // Events routed to the server can't be received by clients
+    let mut ping = PingEvent::new(1);
+    ping.get_event_data_mut().set_from(EventSource::Server);
+    let mut buffer = NetworkBuffer::new();
+    event_registry::serialize_event(ping.as_ref(), &mut buffer);
+    let result = client.receive_remote_event(&mut buffer, None);
+    assert!(matches!(result, Err(RoutingError::WrongDirection(_))));
+
+    assert_eq!(server.get_pending_count(), 0);
+    assert_eq!(client.get_pending_count(), 0);
+
+    // Events can't arrive already cancelled
+    let pings = count::<PingEvent>(&server);
+    let mut ping = PingEvent::new(1);
+    ping.get_event_data_mut().set_from(EventSource::Client);
+    ping.get_event_data_mut().set_cancelled(true);
+    let mut buffer = NetworkBuffer::new();
+    event_registry::serialize_event(ping.as_ref(), &mut buffer);
+    server
+        .receive_remote_event(&mut buffer, Some(token))
+        .unwrap();
+    server.process_events();
+    assert_eq!(*pings.lock().unwrap(), 1);
+}
@@ -0,0 +1,36 @@
+use crate::world::GameClock;
+
+#[test]
+fn game_clock_fixed_steps() {
+    let mut clock = GameClock::new();
+    clock.set_tick_rate(10);
+
+    // 0.25 s worth of steps of 0.1 s
+    assert_eq!(clock.advance(0.25, 1.0), 2);
+    assert!((clock.alpha() - 0.5).abs() < 0.001);
+
+    // The leftover time is carried over
+    assert_eq!(clock.advance(0.06, 1.0), 1);
+}
+
+#[test]
+fn game_clock_catch_up_and_dilation() {
+    let mut clock = GameClock::new();
+    clock.set_tick_rate(10);
+    clock.set_max_catch_up(3);
+
+    // A long hitch is clamped
+    assert_eq!(clock.advance(5.0, 1.0), 3);
+    assert!(clock.alpha() < 1.0);
+
+    // Half speed
+    let mut clock = GameClock::new();
+    clock.set_tick_rate(10);
+    assert_eq!(clock.advance(0.4, 0.5), 2);
+
+    // Paused
+    clock.pause();
+    assert_eq!(clock.advance(1.0, 1.0), 0);
+    clock.resume();
+    assert_eq!(clock.advance(0.1, 1.0), 1);
+}
@@ -0,0 +1,365 @@
+use crate::core::Game;
+use crate::events::GameEvent;
+use crate::settings::Setting::*;
+use crate::settings::{
+    setting_overrides, setting_schema, GameSettings, Setting, SettingChangeRequestEvent,
+    SettingChangedEvent, SettingError, SettingKind, SettingLayer, SettingSchema, SettingsError,
+};
+use crate::utils::logger::Severity;
+use crate::utils::types::*;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn game_settings_round_trip() {
+    let settings = GameSettings::new();
+    let values = [
+        ("test.empty", Empty),
+        ("test.boolean", Boolean(true)),
+        (
+            "test.string",
+            Str(String::from("quote \" backslash \\ line\nbreak = 1")),
+        ),
+        ("test.unsigned", UnsignedInt(u64::MAX)),
+        ("test.signed", SignedInt(-42)),
+        ("test.float", FloatingPoint(0.1)),
+        ("test.ivec2", IVector2(Vector2i::new(-1, 2))),
+        ("test.fvec2", FVector2(Vector2f::new(0.5, -1.25))),
+        ("test.ivec3", IVector3(Vector3i::new(1, 2, 3))),
+        ("test.fvec3", FVector3(Vector3f::new(0.1, 0.2, 0.3))),
+        ("test.ivec4", IVector4(Vector4i::new(1, 2, 3, 4))),
+        ("test.fvec4", FVector4(Vector4f::new(1.0, 2.0, 3.0, 4.5))),
+        ("test.severity", LogSeverity(Severity::Warning)),
+        ("sectionless", Boolean(false)),
+    ];
+    for (key, value) in values.iter() {
+        settings
+            .set_in(SettingLayer::UserConfig, key, value.clone())
+            .unwrap();
+    }
+
+    let text = settings.save_to_string();
+    assert!(text.starts_with("sectionless: bool = false\n"));
+    assert!(text.contains("[test]\n"));
+    assert!(text.contains("ivec2: ivec2 = -1, 2\n"));
+
+    let loaded = GameSettings::new();
+    loaded.load_from_str(&text).unwrap();
+    for (key, value) in values.iter() {
+        assert!(loaded.get(key) == *value, "{} didn't round trip", key);
+    }
+    assert_eq!(loaded.save_to_string(), text);
+}
+
+#[test]
+fn game_settings_parse() {
+    let settings = GameSettings::new();
+    let text = "# Comment\n\n[online]\nport: uint = 9100\n  unknownKey : string = kept as is  \n";
+    settings.load_from_str(text).unwrap();
+    assert!(settings.get("online.port") == UnsignedInt(9100));
+    assert!(settings.get("online.unknownKey") == Str(String::from("kept as is")));
+    assert!(settings
+        .save_to_string()
+        .contains("unknownKey: string = \"kept as is\"\n"));
+
+    // Errors point at the line, and nothing is applied
+    for (text, line) in [
+        ("[online]\nport: uint = -1\n", 2),
+        ("\n\nport = 1\n", 3),
+        ("[online\n", 1),
+        ("port: text = 1\n", 1),
+        ("a: bool = true\nsize: ivec2 = 1, 2, 3\n", 2),
+        ("title: string = \"unterminated\n", 1),
+    ] {
+        match settings.load_from_str(text) {
+            Err(SettingsError::Parse {
+                line: error_line, ..
+            }) => assert_eq!(error_line, line),
+            _ => panic!("{:?} should not parse", text),
+        }
+    }
+    assert!(settings.get("a").is_empty());
+    assert!(settings.get("online.port") == UnsignedInt(9100));
+
+    assert!(Setting::parse(SettingKind::LogSeverity, "fatal") == Ok(LogSeverity(Severity::Fatal)));
+    assert!(Setting::parse(SettingKind::Str, "unquoted") == Ok(Str(String::from("unquoted"))));
+}
+
+#[test]
+fn game_settings_events() {
+    let _registry = super::event_registry_test::REGISTRY.lock().unwrap();
+    let game = Game::builder()
+        .with_name("settings_events")
+        .as_server()
+        .headless()
+        .build();
+
+    // Listeners can veto or rewrite changes
+    let dispatcher = game.get_event_dispatcher();
+    dispatcher
+        .listen(
+            |event: &mut SettingChangeRequestEvent| match event.get_setting_name() {
+                "test.vetoed" | "test.overridden" => event.get_event_data_mut().set_cancelled(true),
+                "test.rewritten" => event.set_new_value(UnsignedInt(2)),
+                _ => {}
+            },
+        )
+        .unwrap();
+    let changed = Arc::new(Mutex::new(Vec::new()));
+    let list = changed.clone();
+    dispatcher
+        .listen(move |event: &mut SettingChangedEvent| {
+            list.lock()
+                .unwrap()
+                .push(event.get_setting_name().to_string());
+        })
+        .unwrap();
+
+    // No events before the game runs
+    let settings = game.get_settings();
+    assert!(settings.set("test.vetoed", Boolean(true)).is_ok());
+    assert!(settings.set_user("test.overridden", Boolean(false)).is_ok());
+    assert!(settings.set_user("test.kept", Boolean(false)).is_ok());
+    assert!(changed.lock().unwrap().is_empty());
+
+    let runner = game.clone();
+    let handle = thread::spawn(move || runner.run());
+    while !game.is_running() {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    assert!(matches!(
+        settings.set("test.vetoed", Boolean(false)),
+        Err(SettingError::Vetoed(_))
+    ));
+    assert!(settings.get("test.vetoed") == Boolean(true));
+    assert!(settings.set("test.rewritten", UnsignedInt(1)).is_ok());
+    assert!(settings.get("test.rewritten") == UnsignedInt(2));
+
+    // Overrides are applied together, or not at all
+    assert!(settings
+        .apply_args(["--set", "test.kept=true", "--set", "test.overridden=true"])
+        .is_err());
+    assert!(settings.get("test.kept") == Boolean(false));
+
+    // The logger follows its settings
+    game.get_logger().set_print_severity(Severity::Fatal);
+    while changed.lock().unwrap().len() < 2 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert!(*changed.lock().unwrap() == vec!["test.rewritten", "log.printSeverity"]);
+    assert!(game.get_logger().get_print_severity() == Severity::Fatal);
+
+    game.stop();
+    handle.join().unwrap().unwrap();
+}
+
+#[test]
+fn game_settings_schema() {
+    let settings = GameSettings::new();
+    assert!(settings.get("engine.tickRate") == UnsignedInt(60));
+
+    // Declared settings only accept valid values
+    assert!(matches!(
+        settings.set("engine.tickRate", Str(String::from("fast"))),
+        Err(SettingError::WrongKind { .. })
+    ));
+    assert!(matches!(
+        settings.set("engine.tickRate", UnsignedInt(0)),
+        Err(SettingError::OutOfRange { .. })
+    ));
+    assert!(settings.get("engine.tickRate") == UnsignedInt(60));
+    assert!(settings.set("engine.tickRate", UnsignedInt(120)).is_ok());
+    assert!(settings.set("test.undeclared", SignedInt(-1)).is_ok());
+
+    // Settings declared later fall back to their default value
+    setting_schema::declare_setting(
+        "test.schema.mode",
+        SettingSchema::new(Str(String::from("fast")), "Test mode")
+            .with_allowed(vec![Str(String::from("fast")), Str(String::from("slow"))])
+            .requiring_restart(),
+    )
+    .unwrap();
+    assert!(matches!(
+        setting_schema::declare_setting("test.schema.mode", SettingSchema::new(Empty, "")),
+        Err(SettingError::AlreadyDeclared(_))
+    ));
+    assert!(settings.get("test.schema.mode") == Str(String::from("fast")));
+    assert!(matches!(
+        settings.set("test.schema.mode", Str(String::from("medium"))),
+        Err(SettingError::NotAllowed(_))
+    ));
+    assert!(settings
+        .set("test.schema.mode", Str(String::from("slow")))
+        .is_ok());
+
+    // Loaded values are validated too
+    match settings.load_from_str("[online]\nport: uint = 0\n") {
+        Err(SettingsError::Parse { line, .. }) => assert_eq!(line, 2),
+        _ => panic!("Port 0 should not load"),
+    }
+
+    // The dump documents every setting and loads back
+    let dump = setting_schema::dump();
+    assert!(dump.contains(
+        "# Fixed simulation steps per second\n# Range: 1 to 1000\n# Requires a restart\ntickRate: uint = 60\n"
+    ));
+    assert!(dump.contains("# Allowed: \"fast\", \"slow\"\n"));
+    let loaded = GameSettings::new();
+    loaded.load_from_str(&dump).unwrap();
+    assert!(loaded.get("engine.tickRate") == UnsignedInt(60));
+
+    // Saved settings are documented the same way
+    let saved = GameSettings::new();
+    saved
+        .load_from_str("[engine]\ntickRate: uint = 30\n")
+        .unwrap();
+    assert_eq!(
+        saved.save_to_string(),
+        "[engine]\n# Fixed simulation steps per second\n# Range: 1 to 1000\n# Requires a restart\ntickRate: uint = 30\n"
+    );
+}
+
+#[test]
+fn game_settings_overrides() {
+    let settings = GameSettings::new();
+    settings
+        .set_in(
+            SettingLayer::GameDefaults,
+            "test.size",
+            IVector2(Vector2i::new(1, 1)),
+        )
+        .unwrap();
+
+    let remaining = settings
+        .apply_args([
+            "--verbose",
+            "--set",
+            "online.port=9100",
+            "--set=test.size=800x600",
+            "level",
+        ])
+        .unwrap();
+    assert_eq!(remaining, vec!["--verbose", "level"]);
+    assert!(settings.get("online.port") == UnsignedInt(9100));
+    assert!(settings.get("test.size") == IVector2(Vector2i::new(800, 600)));
+
+    settings
+        .apply_env_vars([
+            (
+                String::from("SPAGHETTI_ONLINE_MAX_CLIENTS"),
+                String::from("32"),
+            ),
+            (
+                String::from("SPAGHETTI_LOG_PRINT_SEVERITY"),
+                String::from("warning"),
+            ),
+            (String::from("SPAGHETTI_HOME"), String::from("/opt/game")),
+            (String::from("PATH"), String::from("/usr/bin")),
+        ])
+        .unwrap();
+    assert!(settings.get("online.maxClients") == UnsignedInt(32));
+    assert!(settings.get("log.printSeverity") == LogSeverity(Severity::Warning));
+
+    // Invalid overrides change nothing
+    for args in [
+        vec!["--set", "online.port=9200", "--set", "online.port=none"],
+        vec!["--set", "online.port=9200", "--set", "online.port=0"],
+        vec!["--set", "online.port=9200", "--set", "test.unknown=1"],
+        vec!["--set"],
+    ] {
+        assert!(matches!(
+            settings.apply_args(args),
+            Err(SettingsError::InvalidOverride { .. })
+        ));
+    }
+    assert!(matches!(
+        settings.apply_env_vars([(String::from("SPAGHETTI_ONLINE_PORT"), String::from("-1"))]),
+        Err(SettingsError::InvalidOverride { source, .. }) if source == "SPAGHETTI_ONLINE_PORT"
+    ));
+    assert!(settings.get("online.port") == UnsignedInt(9100));
+
+    // Variables that aren't valid unicode are ignored
+    #[cfg(unix)]
+    {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+        let vars = setting_overrides::unicode_vars([
+            (
+                OsString::from("SPAGHETTI_ONLINE_PORT"),
+                OsString::from_vec(vec![0xff]),
+            ),
+            (OsString::from_vec(vec![0xff]), OsString::from("1")),
+            (OsString::from("SPAGHETTI_TEST_SIZE"), OsString::from("2x2")),
+        ]);
+        assert_eq!(
+            vars,
+            vec![(String::from("SPAGHETTI_TEST_SIZE"), String::from("2x2"))]
+        );
+    }
+
+    assert_eq!(
+        setting_overrides::env_var_name("window.fullscreenMonitor"),
+        "SPAGHETTI_WINDOW_FULLSCREEN_MONITOR"
+    );
+}
+
+#[test]
+fn game_settings_layers() {
+    let settings = GameSettings::new();
+    assert!(settings.get_layer_of("online.port") == Some(SettingLayer::EngineDefaults));
+    assert!(settings.get_layer_of("test.layered").is_none());
+
+    // Each layer overrides the ones below
+    for (layer, port) in [
+        (SettingLayer::UserConfig, 9100),
+        (SettingLayer::Runtime, 9300),
+        (SettingLayer::GameDefaults, 9000),
+        (SettingLayer::CommandLine, 9200),
+    ] {
+        settings
+            .set_in(layer, "online.port", UnsignedInt(port))
+            .unwrap();
+    }
+    assert!(settings.get("online.port") == UnsignedInt(9300));
+    assert!(settings.get_layer_of("online.port") == Some(SettingLayer::Runtime));
+    assert!(settings.get_in(SettingLayer::UserConfig, "online.port") == UnsignedInt(9100));
+    assert!(settings.get_in(SettingLayer::EngineDefaults, "online.port") == UnsignedInt(9018));
+
+    // Resetting falls back to the layer below, down to the engine defaults
+    for (layer, port) in [
+        (SettingLayer::Runtime, 9200),
+        (SettingLayer::CommandLine, 9100),
+        (SettingLayer::UserConfig, 9000),
+        (SettingLayer::GameDefaults, 9018),
+    ] {
+        assert!(settings.reset("online.port") == Some(layer));
+        assert!(settings.get("online.port") == UnsignedInt(port));
+    }
+    assert!(settings.reset("online.port").is_none());
+
+    // Only the user config is saved
+    settings
+        .set_in(SettingLayer::GameDefaults, "test.game", Boolean(true))
+        .unwrap();
+    settings
+        .set_in(SettingLayer::UserConfig, "test.user", Boolean(true))
+        .unwrap();
+    settings.set("test.runtime", Boolean(true)).unwrap();
+    settings.apply_args(["--set", "test.user=false"]).unwrap();
+    assert!(settings.get_layer_of("test.user") == Some(SettingLayer::CommandLine));
+
+    // Hidden changes are kept, and tell which layer hides them
+    assert!(settings.set_user("test.user", Boolean(true)).unwrap() == SettingLayer::CommandLine);
+    assert!(settings.get("test.user") == Boolean(false));
+    assert_eq!(settings.save_to_string(), "[test]\nuser: bool = true\n");
+
+    // Loading fills the user config, below command line overrides
+    settings
+        .load_from_str("[test]\nuser: bool = true\ngame: bool = false\n")
+        .unwrap();
+    assert!(settings.get("test.user") == Boolean(false));
+    assert!(settings.get("test.game") == Boolean(false));
+    assert!(settings.get_layer_of("test.game") == Some(SettingLayer::UserConfig));
+}
@@ -0,0 +1,94 @@
+use crate::core::Game;
+use crate::events::NothingHappenedEvent;
+use crate::settings::Setting::*;
+use crate::settings::SettingChangedEvent;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn game_instances() {
+    let server = Game::builder().with_name("server").as_server().build();
+    let client = Game::builder().with_name("client").as_client().build();
+    assert_ne!(server.get_index(), client.get_index());
+
+    // The builder links the calling thread to the last built game
+    assert!(Arc::ptr_eq(
+        &Game::get_instance().upgrade().unwrap(),
+        &client
+    ));
+
+    let found = Game::get_by_index(server.get_index());
+    assert!(Arc::ptr_eq(&found.upgrade().unwrap(), &server));
+    let found = Game::get_by_name("client");
+    assert!(Arc::ptr_eq(&found.upgrade().unwrap(), &client));
+
+    // Threads spawned through the game are linked to it
+    let index = server
+        .spawn_thread("server_worker", || {
+            Game::get_instance().upgrade().map(|game| game.get_index())
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(index, Some(server.get_index()));
+
+    let server_index = server.get_index();
+    drop(server);
+    assert!(Game::get_by_index(server_index).upgrade().is_none());
+}
+
+#[test]
+fn game_run_again() {
+    let _registry = super::event_registry_test::REGISTRY.lock().unwrap();
+    let game = Game::builder()
+        .with_name("run_again")
+        .as_server()
+        .headless()
+        .build();
+    let dispatcher = game.get_event_dispatcher();
+    let events = Arc::new(AtomicU32::new(0));
+    let counter = events.clone();
+    dispatcher
+        .listen(move |_: &mut NothingHappenedEvent| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    let changes = Arc::new(AtomicU32::new(0));
+    let counter = changes.clone();
+    dispatcher
+        .listen(move |_: &mut SettingChangedEvent| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+    // Nothing dispatches events before the game runs
+    let event = dispatcher.raise_event_and_wait(NothingHappenedEvent::new_empty());
+    assert!(event.get_event_data().is_cancelled());
+    assert_eq!(events.load(Ordering::SeqCst), 0);
+
+    for run in 1..=2 {
+        let runner = game.clone();
+        let handle = thread::spawn(move || runner.run());
+        while !game.is_running() || dispatcher.is_shut_down() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // Every run dispatches events the same way
+        let event = dispatcher.raise_event_and_wait(NothingHappenedEvent::new_empty());
+        assert!(!event.get_event_data().is_cancelled());
+        dispatcher.raise_after(NothingHappenedEvent::new_empty(), 1);
+        while events.load(Ordering::SeqCst) < run * 2 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        game.get_settings()
+            .set("test.run", UnsignedInt(run as u64))
+            .unwrap();
+        assert_eq!(changes.load(Ordering::SeqCst), run);
+
+        game.stop();
+        handle.join().unwrap().unwrap();
+        assert!(dispatcher.is_shut_down());
+    }
+}
@@ -0,0 +1,138 @@
+use super::entry_point_test::ENTRY_POINT;
+use crate::core::entry_point::is_main_thread;
+use crate::core::JobSystem;
+use crate::spaghetti_debug_entry_point;
+use std::panic;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+
+#[test]
+fn job_system_scopes() {
+    let jobs = JobSystem::new(3);
+
+    // Scoped jobs can borrow from the stack
+    let mut values: Vec<u32> = (0..1000).collect();
+    jobs.parallel_for_mut(&mut values, |value| *value *= 2);
+    assert!(values.iter().enumerate().all(|(i, v)| *v == i as u32 * 2));
+
+    let sum = AtomicU32::new(0);
+    jobs.parallel_for(&values, |value| {
+        sum.fetch_add(*value, Ordering::SeqCst);
+    });
+    assert_eq!(sum.load(Ordering::SeqCst), 999 * 1000);
+
+    let total = AtomicU32::new(0);
+    jobs.scope(|scope| {
+        for _ in 0..10 {
+            scope.spawn(|| {
+                // Nested scopes must not deadlock the workers
+                jobs.scope(|inner| {
+                    inner.spawn(|| {
+                        total.fetch_add(1, Ordering::SeqCst);
+                    });
+                });
+            });
+        }
+    });
+    assert_eq!(total.load(Ordering::SeqCst), 10);
+
+    // Panics are resumed once every job has completed
+    let finished = AtomicU32::new(0);
+    let caught = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        jobs.scope(|scope| {
+            scope.spawn(|| panic!("scoped job panic"));
+            scope.spawn(|| {
+                finished.fetch_add(1, Ordering::SeqCst);
+            });
+        });
+    }));
+    assert!(caught.is_err());
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn job_system_dependencies() {
+    let jobs = JobSystem::new(2);
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let log = order.clone();
+    let first = jobs.spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        log.lock().unwrap().push(1);
+    });
+    let log = order.clone();
+    let second = jobs.spawn(move || log.lock().unwrap().push(2));
+    let log = order.clone();
+    let last = jobs.spawn_after(&[first.clone(), second.clone()], move || {
+        log.lock().unwrap().push(3);
+    });
+
+    last.wait();
+    assert!(first.is_done() && second.is_done());
+    assert_eq!(order.lock().unwrap().last(), Some(&3));
+
+    let failed = jobs.spawn(|| panic!("job panic"));
+    failed.wait();
+    assert!(failed.has_panicked());
+    assert!(!last.has_panicked());
+}
+
+#[test]
+fn job_system_shutdown() {
+    let jobs = JobSystem::new(1);
+    let (started_sender, started) = mpsc::channel();
+    let (sender, receiver) = mpsc::channel::<()>();
+
+    // Keeps the only worker busy until the queued job is dropped with its sender
+    let running = jobs.spawn(move || {
+        started_sender.send(()).unwrap();
+        let _ = receiver.recv();
+    });
+    started.recv().unwrap();
+    let queued = jobs.spawn(move || drop(sender));
+    let dependent = jobs.spawn_after(&[queued.clone()], || {});
+
+    // Jobs that never ran are cancelled, so waiting on them doesn't block
+    drop(jobs);
+    queued.wait();
+    dependent.wait();
+    assert!(!running.has_panicked());
+    assert!(queued.has_panicked() && dependent.has_panicked());
+
+    // A pool dropped by one of its own jobs doesn't join that worker
+    let jobs = Arc::new(JobSystem::new(1));
+    let (sender, receiver) = mpsc::channel::<Arc<JobSystem>>();
+    let handle = jobs.spawn(move || drop(receiver.recv().unwrap()));
+    sender.send(jobs).unwrap();
+    handle.wait();
+    assert!(!handle.has_panicked());
+}
+
+#[test]
+fn job_system_main_thread() {
+    let _lock = ENTRY_POINT.lock().unwrap();
+    spaghetti_debug_entry_point!(|| {
+        let jobs = JobSystem::global();
+        let ran = Arc::new(AtomicU32::new(0));
+        let on_main = Arc::new(Mutex::new(Vec::new()));
+
+        // Jobs catch their panics, so results are checked on this thread
+        let counter = ran.clone();
+        let threads = on_main.clone();
+        let background = jobs.spawn(move || {
+            threads.lock().unwrap().push(is_main_thread());
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        let counter = ran.clone();
+        let threads = on_main.clone();
+        let main = jobs.spawn_on_main_after(&[background.clone()], move || {
+            threads.lock().unwrap().push(is_main_thread());
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        main.wait();
+        assert!(!background.has_panicked() && !main.has_panicked());
+        assert_eq!(*on_main.lock().unwrap(), vec![false, true]);
+        assert_eq!(ran.load(Ordering::SeqCst), 2);
+    });
+}
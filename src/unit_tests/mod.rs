@@ -1,3 +1,16 @@
+mod component_runner_test;
+mod coroutine_test;
+mod entry_point_test;
+mod event_dispatcher_test;
+mod event_interceptor_test;
+mod event_recording_test;
 mod event_registry_test;
+mod event_routing_test;
+mod game_clock_test;
+mod game_settings_test;
+mod game_test;
+mod job_system_test;
 mod log_test;
 mod mutex_test;
+mod network_buffer_test;
+mod scheduler_test;
@@ -0,0 +1,75 @@
+use crate::events::event_registry;
+use crate::events::{EventSource, GameEvent};
+use crate::networking::{BufferError, NetworkBuffer, Serializable};
+use crate::settings::Setting;
+use crate::settings::SettingChangedEvent;
+use crate::utils::types::*;
+
+#[test]
+fn network_buffer_values() {
+    let mut buffer = NetworkBuffer::new();
+    42u8.write(&mut buffer);
+    (-7i64).write(&mut buffer);
+    1.5f32.write(&mut buffer);
+    String::from("spaghetti").write(&mut buffer);
+    vec![1u16, 2, 3].write(&mut buffer);
+    Some(true).write(&mut buffer);
+    Setting::IVector2(Vector2i::new(800, 600)).write(&mut buffer);
+
+    let mut buffer = NetworkBuffer::from_bytes(buffer.into_bytes());
+    assert_eq!(u8::read(&mut buffer).unwrap(), 42);
+    assert_eq!(i64::read(&mut buffer).unwrap(), -7);
+    assert_eq!(f32::read(&mut buffer).unwrap(), 1.5);
+    assert_eq!(String::read(&mut buffer).unwrap(), "spaghetti");
+    assert_eq!(Vec::<u16>::read(&mut buffer).unwrap(), vec![1, 2, 3]);
+    assert_eq!(Option::<bool>::read(&mut buffer).unwrap(), Some(true));
+    let setting = Setting::read(&mut buffer).unwrap();
+    assert_eq!(
+        setting.as_int_vec2_or(Vector2i::new(0, 0)),
+        Vector2i::new(800, 600)
+    );
+
+    // Nothing left to read
+    assert_eq!(buffer.remaining(), 0);
+    assert!(matches!(
+        u8::read(&mut buffer),
+        Err(BufferError::UnexpectedEnd)
+    ));
+}
+
+#[test]
+fn network_buffer_events() {
+    let mut event = SettingChangedEvent::new(
+        String::from("online.port"),
+        Setting::UnsignedInt(9018),
+        Setting::UnsignedInt(9019),
+    );
+    event.get_event_data_mut().set_from(EventSource::Server);
+    event.get_event_data_mut().set_cancelled(true);
+
+    let mut buffer = NetworkBuffer::new();
+    event_registry::serialize_event(&event, &mut buffer);
+    // Unknown event types are skipped
+    buffer.write_u64(0xdead);
+    buffer.write_u32(3);
+    buffer.write_bytes(&[1, 2, 3]);
+    event_registry::serialize_event(&event, &mut buffer);
+
+    let mut buffer = NetworkBuffer::from_bytes(buffer.into_bytes());
+    let read = event_registry::deserialize_event(&mut buffer).unwrap();
+    assert!(read.get_event_type() == event.get_event_type());
+    assert!(read.get_event_data().get_id() == event.get_event_data().get_id());
+    assert!(read.get_event_data().get_from() == EventSource::Server);
+    assert!(read.get_event_data().is_cancelled());
+
+    let read = read.downcast::<SettingChangedEvent>().ok().unwrap();
+    assert_eq!(read.get_setting_name(), "online.port");
+    assert_eq!(read.get_old_value().as_unsigned_int_or(0), 9018);
+    assert_eq!(read.get_new_value().as_unsigned_int_or(0), 9019);
+
+    assert!(matches!(
+        event_registry::deserialize_event(&mut buffer),
+        Err(BufferError::UnknownEventType(0xdead))
+    ));
+    assert!(event_registry::deserialize_event(&mut buffer).is_ok());
+}
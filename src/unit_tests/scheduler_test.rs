@@ -0,0 +1,108 @@
+use crate::core::Game;
+use crate::events::NothingHappenedEvent;
+use crate::world::GameState;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn scheduler_timers() {
+    let mut state = GameState::new();
+    state.get_clock_mut().set_tick_rate(10);
+
+    let once = Arc::new(AtomicU32::new(0));
+    let repeated = Arc::new(AtomicU32::new(0));
+
+    let counter = once.clone();
+    let scheduler = state.get_scheduler_mut();
+    let handle = scheduler.after(Duration::from_millis(300), move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    let counter = repeated.clone();
+    let repeating = scheduler.every(Duration::from_millis(200), move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+
+    state.advance(0.2);
+    assert_eq!(once.load(Ordering::SeqCst), 0);
+    assert_eq!(repeated.load(Ordering::SeqCst), 1);
+
+    state.advance(0.1);
+    assert_eq!(once.load(Ordering::SeqCst), 1);
+    assert!(!state.get_scheduler().is_scheduled(handle));
+
+    // Paused game time doesn't count
+    state.pause();
+    state.advance(1.0);
+    assert_eq!(repeated.load(Ordering::SeqCst), 1);
+    state.resume();
+
+    // Game time flows twice as fast
+    state.set_tick_multiplier(2.0);
+    state.advance(0.1);
+    assert_eq!(repeated.load(Ordering::SeqCst), 2);
+
+    assert!(state.get_scheduler_mut().cancel(repeating));
+    state.advance(1.0);
+    assert_eq!(repeated.load(Ordering::SeqCst), 2);
+    assert!(state.get_scheduler().is_empty());
+}
+
+#[test]
+fn scheduler_self_cancel() {
+    let mut state = GameState::new();
+    state.get_clock_mut().set_tick_rate(10);
+
+    let count = Arc::new(AtomicU32::new(0));
+    let own_handle = Arc::new(Mutex::new(None));
+
+    let counter = count.clone();
+    let handle_slot = own_handle.clone();
+    let handle = state
+        .get_scheduler_mut()
+        .every(Duration::from_millis(100), move |scheduler| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 2 {
+                // Timers can cancel themselves and schedule new ones
+                let handle = handle_slot.lock().unwrap().unwrap();
+                assert!(scheduler.cancel(handle));
+
+                let counter = counter.clone();
+                scheduler.after(Duration::from_millis(100), move |_| {
+                    counter.fetch_add(10, Ordering::SeqCst);
+                });
+            }
+        });
+    *own_handle.lock().unwrap() = Some(handle);
+
+    state.advance(0.3);
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+    assert_eq!(state.get_scheduler().len(), 1);
+
+    state.advance(0.2);
+    assert_eq!(count.load(Ordering::SeqCst), 13);
+    assert!(state.get_scheduler().is_empty());
+}
+
+#[test]
+fn scheduler_owning_game() {
+    let owner = Game::builder().with_name("scheduler_owner").build();
+    // The builder links this thread to the last built game
+    let other = Game::builder().with_name("scheduler_other").build();
+    owner.get_event_dispatcher().restart();
+    other.get_event_dispatcher().restart();
+
+    let mut state = owner.get_game_state_mut();
+    state.get_clock_mut().set_tick_rate(10);
+    state
+        .get_scheduler_mut()
+        .raise_after(Duration::from_millis(100), NothingHappenedEvent::new_empty);
+    state.advance(0.1);
+    drop(state);
+
+    // Events are raised through the game that owns the scheduler
+    assert_eq!(owner.get_event_dispatcher().get_pending_count(), 1);
+    assert_eq!(other.get_event_dispatcher().get_pending_count(), 0);
+
+    owner.get_event_dispatcher().shutdown();
+    other.get_event_dispatcher().shutdown();
+}
@@ -1,5 +1,7 @@
 use crate::core::Game;
+use crate::events::EventDispatcher;
 use crate::settings::Setting::LogSeverity;
+use crate::settings::SettingChangedEvent;
 use crate::utils::logger::Severity::*;
 use chrono::{Datelike, Timelike, Utc};
 use once_cell::sync::Lazy;
@@ -373,6 +375,7 @@ impl Logger {
         // Retrieve some data
         let is_game;
         let game_index;
+        let game_name;
         let thread = thread::current();
         let thread_name = thread.name().unwrap_or("*unnamed_thread*");
         let date = Utc::now();
@@ -380,9 +383,11 @@ impl Logger {
         if let Some(game) = self.game.upgrade() {
             is_game = true;
             game_index = game.get_index();
+            game_name = game.get_name().to_string();
         } else {
             is_game = false;
             game_index = 0;
+            game_name = String::new();
         }
 
         write!(
@@ -400,6 +405,9 @@ impl Logger {
 
         if is_game {
             write!(device, " {}", game_index)?;
+            if !game_name.is_empty() {
+                write!(device, " {}", game_name)?;
+            }
         }
 
         write!(device, "][{}][{}]", thread_name, message_severity)?;
@@ -471,6 +479,22 @@ impl Logger {
         }
     }
 
+    /// Keeps the severities of this logger in sync with
+    /// the `log.printSeverity` and `log.fileSeverity` settings
+    ///
+    /// # Arguments
+    /// * `dispatcher` - The event dispatcher of the game owning the settings
+    pub(crate) fn listen_to_settings(self: &Arc<Self>, dispatcher: &EventDispatcher) {
+        dispatcher.listen_bound(self, |logger: &Logger, event: &mut SettingChangedEvent| {
+            let severity = event.get_new_value().as_log_severity_or(MIN_SEVERITY);
+            match event.get_setting_name() {
+                "log.printSeverity" => logger.data.lock().unwrap().print_severity = severity,
+                "log.fileSeverity" => logger.data.lock().unwrap().file_severity = severity,
+                _ => {}
+            }
+        });
+    }
+
     /// Retrieves the minimum severity this logger
     /// requires for messages to be printed to
     /// standard output
@@ -489,7 +513,9 @@ impl Logger {
     /// * `print_severity` - The new severity
     pub fn set_print_severity(&self, print_severity: Severity) {
         if let Some(game) = self.game.upgrade() {
-            game.get_settings()
+            // Listeners may veto the change
+            let _ = game
+                .get_settings()
                 .set("log.printSeverity", LogSeverity(print_severity));
         } else {
             self.data.lock().unwrap().print_severity = print_severity;
@@ -512,7 +538,9 @@ impl Logger {
     /// * `file_severity` - The new severity
     pub fn set_file_severity(&self, file_severity: Severity) {
         if let Some(game) = self.game.upgrade() {
-            game.get_settings()
+            // Listeners may veto the change
+            let _ = game
+                .get_settings()
                 .set("log.fileSeverity", LogSeverity(file_severity));
         } else {
             self.data.lock().unwrap().file_severity = file_severity;
@@ -6,6 +6,8 @@ pub mod types;
 pub mod request_pipe;
 pub mod new_empty;
 pub mod is_locked;
+pub mod panics;
 
 pub use logger::Logger;
-pub use is_locked::IsLocked;
\ No newline at end of file
+pub use is_locked::IsLocked;
+pub use panics::PanicPolicy;
\ No newline at end of file
@@ -0,0 +1,27 @@
+use std::any::Any;
+
+/// What to do with a callback after it panicked
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PanicPolicy {
+    /// Keep calling it
+    Keep,
+    /// Remove it, it will not be called again
+    Unregister,
+}
+
+/// Extracts the message of a panic, if it has one
+///
+/// # Arguments
+/// * `payload` - The payload obtained by catching the panic
+///
+/// # Returns
+/// * The panic message
+pub fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("*no message*")
+    }
+}
@@ -1,18 +1,20 @@
 use crate::input::InputDispatcher;
 use crate::log;
 use crate::settings::GameSettings;
+use crate::settings::Setting;
 use crate::settings::Setting::Str;
+use crate::settings::SettingChangedEvent;
 use crate::utils::file_util;
 use crate::utils::request_pipe::RequestPipe;
-use crate::utils::types::Vector2i;
+use crate::utils::types::*;
 use crate::window::packets::{GlfwPacket, WindowPacket};
 use crate::window::window_manager::*;
 use crate::window::{cursor_mode, window_manager, VsyncMode, WindowMonitor};
 use glfw::{Context, ffi, RenderContext, SwapInterval, WindowHint};
 use image::RgbaImage;
 use std::path::Path;
-use std::{ptr, sync};
-use std::sync::Arc;
+use std::{mem, ptr, sync};
+use std::sync::{Arc, Mutex};
 
 macro_rules! glfw_request {
     ($packet:ident ( $($request_args:expr),+ ), $response:ident ( $($response_args:ident),+ ), $ret:expr) => {{
@@ -85,6 +87,8 @@ pub struct GameWindow {
     fullscreen: bool,
     saved_size: (i32, i32),
     saved_pos: (i32, i32),
+    // Settings of the game changed since the last swap, filled by a listener
+    setting_changes: ArcMutexVec<(String, Setting)>,
 }
 
 impl GameWindow {
@@ -168,8 +172,24 @@ impl GameWindow {
             fullscreen: false,
             saved_size: (windowed_size.x, windowed_size.y),
             saved_pos,
+            setting_changes: Arc::new(Mutex::new(Vec::new())),
         };
 
+        // Follow the settings of the game owning them, for as long as the window exists
+        if let Some(game) = settings.get_game() {
+            game.get_event_dispatcher().listen_bound(
+                &game_window.setting_changes,
+                |changes: &MutexVec<(String, Setting)>, event: &mut SettingChangedEvent| {
+                    if event.get_setting_name().starts_with("window.") {
+                        changes.lock().unwrap().push((
+                            event.get_setting_name().to_string(),
+                            event.get_new_value().clone(),
+                        ));
+                    }
+                },
+            );
+        }
+
         // Apply some last settings
         game_window.set_size_limits((min_size.x, min_size.y, max_size.x, max_size.y));
 
@@ -206,6 +226,40 @@ impl GameWindow {
         Ok(game_window)
     }
 
+    /// Applies a changed `window.*` setting.
+    /// Settings only read when creating the window are ignored
+    ///
+    /// # Arguments
+    /// * `setting_name` - The name of the setting
+    /// * `value` - The new value
+    pub fn apply_setting(&mut self, setting_name: &str, value: &Setting) {
+        match (setting_name, value) {
+            ("window.size", Setting::IVector2(size)) => self.set_size((size.x, size.y)),
+            ("window.resizable", Setting::Boolean(resizable)) => self.set_resizable(*resizable),
+            ("window.maximized", Setting::Boolean(maximized)) => self.set_maximized(*maximized),
+            ("window.title", Setting::Str(title)) => self.set_title(title.clone()),
+            ("window.vsync", Setting::Vsync(vsync)) => self.set_vsync(*vsync),
+            ("window.minimumSize", Setting::IVector2(minimum)) => {
+                let limits = self.get_size_limits();
+                self.set_size_limits((minimum.x, minimum.y, limits.2, limits.3));
+            }
+            ("window.maximumSize", Setting::IVector2(maximum)) => {
+                let limits = self.get_size_limits();
+                self.set_size_limits((limits.0, limits.1, maximum.x, maximum.y));
+            }
+            _ => {}
+        }
+    }
+
+    /// Applies the `window.*` settings of the game owning the settings the window was created
+    /// with, that changed since the last call. Called by [`GameWindow::swap`]
+    pub fn apply_setting_changes(&mut self) {
+        let changes = mem::take(&mut *self.setting_changes.lock().unwrap());
+        for (setting_name, value) in changes.iter() {
+            self.apply_setting(setting_name, value);
+        }
+    }
+
     /// # Returns
     /// * Whether or not the window is resizable
     pub fn is_resizable(&self) -> bool {
@@ -358,8 +412,10 @@ impl GameWindow {
         .unwrap()
     }
 
-    /// Swap the back and front buffers of the window
+    /// Swap the back and front buffers of the window,
+    /// after applying the settings that changed since the last swap
     pub fn swap(&mut self) {
+        self.apply_setting_changes();
         self.render_context.swap_buffers();
     }
 
@@ -1,5 +1,4 @@
-use crate::utils::types::float;
-use crate::world::{BeginEndPlay, BeginError, GameMode, Update};
+use crate::world::{BeginEndPlay, BeginError, GameClock, GameMode, Update};
 
 pub struct EmptyGameMode {}
 
@@ -10,7 +9,7 @@ impl EmptyGameMode {
 }
 
 impl Update for EmptyGameMode {
-    fn update(&mut self, _delta: float) {}
+    fn update(&mut self, _clock: &GameClock) {}
 }
 
 impl BeginEndPlay for EmptyGameMode {
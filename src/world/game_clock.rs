@@ -0,0 +1,132 @@
+use crate::utils::types::float;
+
+static DEFAULT_TICK_RATE: u64 = 60;
+static DEFAULT_MAX_CATCH_UP: u32 = 5;
+
+/// Keeps track of game time and splits real time into fixed simulation steps.
+///
+/// Real time is scaled by the time dilation and accumulated, then consumed
+/// in steps of a fixed length. The fraction of a step left in the accumulator
+/// is exposed as an interpolation factor for rendering
+pub struct GameClock {
+    step: float,
+    max_catch_up: u32,
+    accumulator: float,
+    alpha: float,
+    real_delta: float,
+    time_dilation: float,
+    paused: bool,
+    tick: u64,
+    time: f64,
+}
+
+impl GameClock {
+    pub fn new() -> Self {
+        Self {
+            step: 1.0 / DEFAULT_TICK_RATE as float,
+            max_catch_up: DEFAULT_MAX_CATCH_UP,
+            accumulator: 0.0,
+            alpha: 0.0,
+            real_delta: 0.0,
+            time_dilation: 1.0,
+            paused: false,
+            tick: 0,
+            time: 0.0,
+        }
+    }
+
+    /// Feeds real elapsed time into the clock
+    ///
+    /// # Arguments
+    /// * `real_delta` - The real time elapsed since the last call, in seconds
+    /// * `time_dilation` - How fast game time flows relative to real time
+    ///
+    /// # Returns
+    /// * The amount of fixed steps that must be simulated, each one
+    /// to be preceded by a call to [`GameClock::begin_step`]
+    pub fn advance(&mut self, real_delta: float, time_dilation: float) -> u32 {
+        self.real_delta = real_delta;
+        self.time_dilation = time_dilation.max(0.0);
+        if self.paused {
+            return 0;
+        }
+
+        self.accumulator += real_delta.max(0.0) * self.time_dilation;
+        let mut steps = (self.accumulator / self.step) as u32;
+        if steps > self.max_catch_up {
+            // Too far behind, drop the time we can't catch up with
+            steps = self.max_catch_up;
+            self.accumulator = self.accumulator % self.step + self.step * steps as float;
+        }
+        self.accumulator -= self.step * steps as float;
+        self.alpha = self.accumulator / self.step;
+        steps
+    }
+
+    /// Moves game time forward by one fixed step
+    pub fn begin_step(&mut self) {
+        self.tick += 1;
+        self.time += self.step as f64;
+    }
+
+    /// The duration of a simulation step in game time, in seconds
+    pub fn delta(&self) -> float {
+        self.step
+    }
+
+    /// How far between the last simulated step and the next one
+    /// the current real time is, between 0 and 1
+    pub fn alpha(&self) -> float {
+        self.alpha
+    }
+
+    /// The real time that was fed to the clock the last time it advanced, in seconds
+    pub fn real_delta(&self) -> float {
+        self.real_delta
+    }
+
+    /// The number of steps simulated so far
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// The game time elapsed so far, in seconds
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn time_dilation(&self) -> float {
+        self.time_dilation
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn get_tick_rate(&self) -> float {
+        1.0 / self.step
+    }
+
+    /// Changes how many fixed steps are simulated per second of game time
+    pub fn set_tick_rate(&mut self, tick_rate: u64) {
+        self.step = 1.0 / tick_rate.max(1) as float;
+    }
+
+    pub fn get_max_catch_up(&self) -> u32 {
+        self.max_catch_up
+    }
+
+    /// Changes the maximum number of steps that can be simulated in one go
+    /// when the simulation is running behind
+    pub fn set_max_catch_up(&mut self, max_catch_up: u32) {
+        self.max_catch_up = max_catch_up.max(1);
+    }
+}
@@ -1 +1,48 @@
-pub struct GameObject {}
+use crate::core::coroutine::{CoroutineContext, CoroutineHandle, Coroutines};
+use crate::world::{GameClock, Update};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub struct GameObject {
+    coroutines: Mutex<Coroutines>,
+}
+
+impl Update for GameObject {
+    fn update(&mut self, clock: &GameClock) {
+        let coroutines = self.coroutines.get_mut().unwrap();
+        coroutines.resume(Duration::from_secs_f64(clock.delta() as f64));
+    }
+}
+
+impl GameObject {
+    pub fn new() -> Self {
+        Self {
+            coroutines: Mutex::new(Coroutines::new()),
+        }
+    }
+
+    /// Starts a coroutine bound to this object, resumed every time the object
+    /// is updated. Waiting for time or ticks is done in game time
+    ///
+    /// # Arguments
+    /// * `producer` - Creates the body of the coroutine from its context
+    ///
+    /// # Returns
+    /// * The handle to stop the coroutine with
+    pub fn start_coroutine<F, R>(&self, producer: F) -> CoroutineHandle
+    where
+        F: FnOnce(CoroutineContext) -> R,
+        R: Future<Output = ()> + Send + 'static,
+    {
+        self.coroutines.lock().unwrap().start(producer)
+    }
+
+    pub fn stop_coroutine(&self, handle: CoroutineHandle) -> bool {
+        self.coroutines.lock().unwrap().cancel(handle)
+    }
+
+    pub fn is_coroutine_running(&self, handle: CoroutineHandle) -> bool {
+        self.coroutines.lock().unwrap().is_running(handle)
+    }
+}
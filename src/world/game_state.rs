@@ -1,9 +1,12 @@
+use crate::core::JobSystem;
 use crate::input::controller::Controller;
 use crate::networking::token::Token;
 use crate::utils::types::*;
 use crate::world::empty_game_mode::EmptyGameMode;
+use crate::world::game_clock::GameClock;
 use crate::world::game_mode::GameMode;
 use crate::world::level::Level;
+use crate::world::scheduler::Scheduler;
 use crate::world::{BeginEndPlay, Update};
 use std::collections::HashMap;
 
@@ -13,11 +16,31 @@ pub struct GameState {
     levels: HashMap<String, Level>,
     players: HashMap<Token, Controller>,
     tick_multiplier: float,
+    clock: GameClock,
+    scheduler: Scheduler,
     needs_replication: bool,
 }
 
-impl Update for GameState {
-    fn update(&mut self, delta: float) {
+impl GameState {
+    pub fn new() -> Self {
+        Self {
+            game_mode: Box::new(EmptyGameMode::new()),
+            game_mode_initialized: false,
+            levels: HashMap::new(),
+            players: HashMap::new(),
+            tick_multiplier: 1.0,
+            clock: GameClock::new(),
+            scheduler: Scheduler::new(),
+            needs_replication: true,
+        }
+    }
+
+    /// Advances the game clock by the given real time, then simulates
+    /// as many fixed steps as the clock requires
+    ///
+    /// # Arguments
+    /// * `real_delta` - The real time elapsed since the last call, in seconds
+    pub fn advance(&mut self, real_delta: float) {
         // Check if the game mode needs initialization
         if !self.game_mode_initialized {
             match self.game_mode.on_begin_play() {
@@ -31,25 +54,21 @@ impl Update for GameState {
             }
         }
 
-        self.game_mode.update(delta);
+        let steps = self.clock.advance(real_delta, self.tick_multiplier);
+        for _ in 0..steps {
+            self.clock.begin_step();
 
-        for level in self.levels.iter_mut() {
-            if level.1.is_active() {
-                level.1.update(delta);
-            }
-        }
-    }
-}
+            self.scheduler.update(&self.clock);
+            self.game_mode.update(&self.clock);
 
-impl GameState {
-    pub fn new() -> Self {
-        Self {
-            game_mode: Box::new(EmptyGameMode::new()),
-            game_mode_initialized: false,
-            levels: HashMap::new(),
-            players: HashMap::new(),
-            tick_multiplier: 1.0,
-            needs_replication: true,
+            // Levels don't share any state, so they can be updated in parallel
+            let clock = &self.clock;
+            let mut active: Vec<&mut Level> = self
+                .levels
+                .values_mut()
+                .filter(|level| level.is_active())
+                .collect();
+            JobSystem::global().parallel_for_mut(&mut active, |level| level.update(clock));
         }
     }
 
@@ -58,6 +77,7 @@ impl GameState {
             self.game_mode.on_end_play();
             self.game_mode_initialized = false;
         }
+        self.scheduler.clear();
     }
 
     pub fn get_level_count(&self) -> usize {
@@ -128,6 +148,44 @@ impl GameState {
         self.levels.get_mut(name)
     }
 
+    pub fn get_clock(&self) -> &GameClock {
+        &self.clock
+    }
+
+    pub fn get_clock_mut(&mut self) -> &mut GameClock {
+        &mut self.clock
+    }
+
+    pub fn get_scheduler(&self) -> &Scheduler {
+        &self.scheduler
+    }
+
+    pub fn get_scheduler_mut(&mut self) -> &mut Scheduler {
+        &mut self.scheduler
+    }
+
+    pub fn get_tick_multiplier(&self) -> float {
+        self.tick_multiplier
+    }
+
+    /// Changes how fast game time flows relative to real time
+    pub fn set_tick_multiplier(&mut self, tick_multiplier: float) {
+        self.tick_multiplier = tick_multiplier;
+        self.needs_replication = true;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.clock.is_paused()
+    }
+
+    pub fn pause(&mut self) {
+        self.clock.pause();
+    }
+
+    pub fn resume(&mut self) {
+        self.clock.resume();
+    }
+
     pub fn set_game_mode(&mut self, game_mode: Box<dyn GameMode>) {
         if self.game_mode_initialized {
             self.game_mode.on_end_play();
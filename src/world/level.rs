@@ -1,5 +1,4 @@
-use crate::utils::types::float;
-use crate::world::{BeginEndPlay, BeginError, Update};
+use crate::world::{BeginEndPlay, BeginError, GameClock, Update};
 
 pub struct Level {
     name: String,
@@ -7,7 +6,7 @@ pub struct Level {
 }
 
 impl Update for Level {
-    fn update(&mut self, delta: float) {}
+    fn update(&mut self, _clock: &GameClock) {}
 }
 
 impl BeginEndPlay for Level {
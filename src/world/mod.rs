@@ -1,18 +1,23 @@
 pub mod begin_end_play;
 pub mod client_state;
 pub mod empty_game_mode;
+pub mod game_clock;
 pub mod game_component;
 pub mod game_mode;
 pub mod game_object;
 pub mod game_state;
 pub mod level;
+pub mod scheduler;
 pub mod update;
 
 pub use begin_end_play::BeginEndPlay;
 pub use begin_end_play::BeginError;
 pub use client_state::ClientState;
+pub use game_clock::GameClock;
 pub use game_component::GameComponent;
 pub use game_mode::GameMode;
 pub use game_object::GameObject;
 pub use game_state::GameState;
+pub use scheduler::Scheduler;
+pub use scheduler::TimerHandle;
 pub use update::Update;
@@ -0,0 +1,248 @@
+use crate::core::Game;
+use crate::events::GameEvent;
+use crate::log;
+use crate::utils::id_type::id_type;
+use crate::world::{GameClock, Update};
+use std::hash::Hasher;
+use std::mem;
+use std::sync::Weak;
+use std::time::Duration;
+
+// Steps are accumulated as floats, timers due within this margin fire right away
+static EPSILON: f64 = 0.000001;
+
+id_type!(TimerHandle);
+
+/// What a timer does when it fires
+pub enum TimerAction {
+    /// Calls the closure, which can schedule or cancel other timers
+    Call(Box<dyn FnMut(&mut Scheduler) + Send + Sync>),
+    /// Raises the event returned by the closure through the
+    /// dispatcher of the game that owns the scheduler
+    Raise(Box<dyn Fn() -> Box<dyn GameEvent> + Send + Sync>),
+}
+
+struct Timer {
+    handle: TimerHandle,
+    remaining: f64,
+    interval: Option<f64>,
+    action: TimerAction,
+    game: Weak<Game>,
+}
+
+/// Runs actions after a delay or at regular intervals of game time.
+///
+/// The scheduler is advanced once per simulated step, so its timers
+/// stop while the game is paused and follow the tick multiplier
+pub struct Scheduler {
+    timers: Vec<Timer>,
+    firing: Option<TimerHandle>,
+    firing_cancelled: bool,
+    game: Option<Weak<Game>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            timers: Vec::new(),
+            firing: None,
+            firing_cancelled: false,
+            game: None,
+        }
+    }
+
+    /// Sets the game that owns the scheduler, events raised by its
+    /// timers go through this game's dispatcher.
+    ///
+    /// Schedulers without an owner use the game linked to the thread
+    /// each timer was created on
+    pub(crate) fn link_game(&mut self, game: Weak<Game>) {
+        self.game = Some(game);
+    }
+
+    /// Calls a closure once, after the given amount of game time
+    ///
+    /// # Arguments
+    /// * `delay` - The game time to wait for
+    /// * `f` - The closure
+    ///
+    /// # Returns
+    /// * The handle to cancel the timer with
+    pub fn after<F>(&mut self, delay: Duration, f: F) -> TimerHandle
+    where
+        F: FnMut(&mut Scheduler) + Send + Sync + 'static,
+    {
+        self.schedule(delay, None, TimerAction::Call(Box::new(f)))
+    }
+
+    /// Calls a closure repeatedly, every time the given amount of game time elapses
+    ///
+    /// # Arguments
+    /// * `interval` - The game time between two calls
+    /// * `f` - The closure
+    ///
+    /// # Returns
+    /// * The handle to cancel the timer with
+    pub fn every<F>(&mut self, interval: Duration, f: F) -> TimerHandle
+    where
+        F: FnMut(&mut Scheduler) + Send + Sync + 'static,
+    {
+        self.schedule(interval, Some(interval), TimerAction::Call(Box::new(f)))
+    }
+
+    /// Raises an event once, after the given amount of game time
+    ///
+    /// # Arguments
+    /// * `delay` - The game time to wait for
+    /// * `factory` - Creates the event to raise
+    ///
+    /// # Returns
+    /// * The handle to cancel the timer with
+    pub fn raise_after<F>(&mut self, delay: Duration, factory: F) -> TimerHandle
+    where
+        F: Fn() -> Box<dyn GameEvent> + Send + Sync + 'static,
+    {
+        self.schedule(delay, None, TimerAction::Raise(Box::new(factory)))
+    }
+
+    /// Raises an event repeatedly, every time the given amount of game time elapses
+    ///
+    /// # Arguments
+    /// * `interval` - The game time between two events
+    /// * `factory` - Creates the event to raise
+    ///
+    /// # Returns
+    /// * The handle to cancel the timer with
+    pub fn raise_every<F>(&mut self, interval: Duration, factory: F) -> TimerHandle
+    where
+        F: Fn() -> Box<dyn GameEvent> + Send + Sync + 'static,
+    {
+        self.schedule(
+            interval,
+            Some(interval),
+            TimerAction::Raise(Box::new(factory)),
+        )
+    }
+
+    /// Schedules a timer
+    ///
+    /// # Arguments
+    /// * `delay` - The game time before the first time the timer fires
+    /// * `interval` - The game time between two firings, or `None` to fire only once
+    /// * `action` - What the timer does when it fires
+    ///
+    /// # Returns
+    /// * The handle to cancel the timer with
+    pub fn schedule(
+        &mut self,
+        delay: Duration,
+        interval: Option<Duration>,
+        action: TimerAction,
+    ) -> TimerHandle {
+        let handle = TimerHandle::new();
+        self.timers.push(Timer {
+            handle,
+            remaining: delay.as_secs_f64(),
+            interval: interval.map(|interval| interval.as_secs_f64()),
+            action,
+            game: self.game.clone().unwrap_or_else(Game::get_instance),
+        });
+        handle
+    }
+
+    /// Stops a timer, it will not fire anymore
+    ///
+    /// # Returns
+    /// * Whether the timer was found
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        if self.firing == Some(handle) {
+            self.firing_cancelled = true;
+            return true;
+        }
+        let count = self.timers.len();
+        self.timers.retain(|timer| timer.handle != handle);
+        count != self.timers.len()
+    }
+
+    pub fn is_scheduled(&self, handle: TimerHandle) -> bool {
+        (self.firing == Some(handle) && !self.firing_cancelled)
+            || self.timers.iter().any(|timer| timer.handle == handle)
+    }
+
+    /// The game time left before a timer fires
+    ///
+    /// # Returns
+    /// * The time left, or `None` if the timer isn't scheduled
+    pub fn get_remaining(&self, handle: TimerHandle) -> Option<Duration> {
+        self.timers
+            .iter()
+            .find(|timer| timer.handle == handle)
+            .map(|timer| Duration::from_secs_f64(timer.remaining.max(0.0)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.timers.clear();
+    }
+
+    fn fire(&mut self, timer: &mut Timer) {
+        match &mut timer.action {
+            TimerAction::Call(f) => f(self),
+            TimerAction::Raise(factory) => match timer.game.upgrade() {
+                Some(game) => game.get_event_dispatcher().raise_event(factory(), true),
+                None => log!(Warning, "Timer tried to raise an event outside of a game"),
+            },
+        }
+    }
+}
+
+impl Update for Scheduler {
+    fn update(&mut self, clock: &GameClock) {
+        let delta = clock.delta() as f64;
+        for timer in self.timers.iter_mut() {
+            timer.remaining -= delta;
+        }
+
+        // Due timers are taken out, so that their closures can use the scheduler
+        let (due, waiting): (Vec<Timer>, Vec<Timer>) = mem::take(&mut self.timers)
+            .into_iter()
+            .partition(|timer| timer.remaining <= EPSILON);
+        self.timers = waiting;
+
+        for mut timer in due {
+            self.firing = Some(timer.handle);
+            self.firing_cancelled = false;
+
+            loop {
+                self.fire(&mut timer);
+                match timer.interval {
+                    // Catch up with every interval that elapsed during the step
+                    Some(interval) if interval > 0.0 => {
+                        timer.remaining += interval;
+                        if timer.remaining > EPSILON || self.firing_cancelled {
+                            break;
+                        }
+                    }
+                    Some(_) => {
+                        timer.remaining = 0.0;
+                        break;
+                    }
+                    None => break,
+                }
+            }
+
+            if timer.interval.is_some() && !self.firing_cancelled {
+                self.timers.push(timer);
+            }
+        }
+        self.firing = None;
+        self.firing_cancelled = false;
+    }
+}
@@ -1,5 +1,5 @@
-use crate::utils::types::*;
+use crate::world::GameClock;
 
 pub trait Update {
-    fn update(&mut self, delta: float);
+    fn update(&mut self, clock: &GameClock);
 }
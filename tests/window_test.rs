@@ -1,4 +1,5 @@
 use once_cell::sync::Lazy;
+use spaghetti_engine::core::Game;
 use spaghetti_engine::settings::GameSettings;
 use spaghetti_engine::settings::Setting::*;
 use spaghetti_engine::utils::types::Vector2i;
@@ -6,28 +7,34 @@ use spaghetti_engine::window::{GameWindow, VsyncMode, WindowMonitor};
 use spaghetti_engine::{log, spaghetti_debug_entry_point};
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 
 static DEFAULT_SETTINGS: Lazy<GameSettings> = Lazy::new(|| {
     let obj = GameSettings::new();
     obj.set(
         "window.fullscreenResolution",
         IVector2(Vector2i::new(1920, 1080)),
-    );
-    obj.set("window.fullscreenMonitor", SignedInt(0));
-    obj.set("window.size", IVector2(Vector2i::new(256, 256)));
-    obj.set("window.minimumSize", IVector2(Vector2i::new(256, 256)));
-    obj.set("window.maximumSize", IVector2(Vector2i::new(-1, -1))); // No max size
-    obj.set("window.fullscreen", Boolean(false));
-    obj.set("window.resizable", Boolean(true));
-    obj.set("window.maximized", Boolean(false));
-    obj.set("window.vsync", Vsync(VsyncMode::Enabled));
-    obj.set("window.transparent", Boolean(false));
-
-    obj.set("window.debugContext", Boolean(true));
-
-    obj.set("window.title", Str(String::from("Spaghetti game")));
-    obj.set("window.icon16", Empty);
-    obj.set("window.icon32", Empty);
+    )
+    .unwrap();
+    obj.set("window.fullscreenMonitor", SignedInt(0)).unwrap();
+    obj.set("window.size", IVector2(Vector2i::new(256, 256)))
+        .unwrap();
+    obj.set("window.minimumSize", IVector2(Vector2i::new(256, 256)))
+        .unwrap();
+    obj.set("window.maximumSize", IVector2(Vector2i::new(-1, -1)))
+        .unwrap(); // No max size
+    obj.set("window.fullscreen", Boolean(false)).unwrap();
+    obj.set("window.resizable", Boolean(true)).unwrap();
+    obj.set("window.maximized", Boolean(false)).unwrap();
+    obj.set("window.vsync", Vsync(VsyncMode::Enabled)).unwrap();
+    obj.set("window.transparent", Boolean(false)).unwrap();
+
+    obj.set("window.debugContext", Boolean(true)).unwrap();
+
+    obj.set("window.title", Str(String::from("Spaghetti game")))
+        .unwrap();
+    obj.set("window.icon16", Empty).unwrap();
+    obj.set("window.icon32", Empty).unwrap();
 
     obj
 });
@@ -45,14 +52,14 @@ fn window() {
         // Fullscreen
         {
             let settings = settings_clone();
-            settings.set("window.fullscreen", Boolean(true));
+            settings.set("window.fullscreen", Boolean(true)).unwrap();
             init_window(&settings);
         }
 
         // Maximized
         {
             let settings = settings_clone();
-            settings.set("window.maximized", Boolean(true));
+            settings.set("window.maximized", Boolean(true)).unwrap();
             let window = init_window(&settings);
             assert!(window.is_maximized());
         }
@@ -60,7 +67,7 @@ fn window() {
         // Resizable
         {
             let settings = settings_clone();
-            settings.set("window.resizable", Boolean(false));
+            settings.set("window.resizable", Boolean(false)).unwrap();
             let window = init_window(&settings);
             assert!(!window.is_resizable());
         }
@@ -68,7 +75,7 @@ fn window() {
         // Debug context
         {
             let settings = settings_clone();
-            settings.set("window.debugContext", Boolean(true));
+            settings.set("window.debugContext", Boolean(true)).unwrap();
             let window = init_window(&settings);
             assert!(window.is_debug_context());
         }
@@ -76,7 +83,7 @@ fn window() {
         // Transparency
         {
             let settings = settings_clone();
-            settings.set("window.transparent", Boolean(true));
+            settings.set("window.transparent", Boolean(true)).unwrap();
             let window = init_window(&settings);
             assert!(window.is_transparent());
         }
@@ -84,8 +91,12 @@ fn window() {
         // Window size limits (positive)
         {
             let settings = settings_clone();
-            settings.set("window.minimumSize", IVector2(Vector2i::new(100, 100)));
-            settings.set("window.maximumSize", IVector2(Vector2i::new(500, 500)));
+            settings
+                .set("window.minimumSize", IVector2(Vector2i::new(100, 100)))
+                .unwrap();
+            settings
+                .set("window.maximumSize", IVector2(Vector2i::new(500, 500)))
+                .unwrap();
             let window = init_window(&settings);
             assert_eq!(window.get_size_limits(), (100, 100, 500, 500));
         }
@@ -93,8 +104,12 @@ fn window() {
         // Window size limits (negative)
         {
             let settings = settings_clone();
-            settings.set("window.minimumSize", IVector2(Vector2i::new(-4, 100)));
-            settings.set("window.maximumSize", IVector2(Vector2i::new(500, 100)));
+            settings
+                .set("window.minimumSize", IVector2(Vector2i::new(-4, 100)))
+                .unwrap();
+            settings
+                .set("window.maximumSize", IVector2(Vector2i::new(500, 100)))
+                .unwrap();
             let window = init_window(&settings);
             assert_eq!(window.get_size_limits(), (-1, -1, 500, 100));
         }
@@ -103,7 +118,7 @@ fn window() {
     // Window settings
     spaghetti_debug_entry_point!(|| {
         let settings = settings_clone();
-        settings.set("window.transparent", Boolean(true));
+        settings.set("window.transparent", Boolean(true)).unwrap();
         let mut window = init_window(&settings);
 
         let _ = window.set_fullscreen_primary((1920, 1080));
@@ -138,6 +153,33 @@ fn window() {
         .join()
         .unwrap();
     });
+
+    // Setting changes
+    spaghetti_debug_entry_point!(|| {
+        let game = Game::builder()
+            .with_name("window_settings")
+            .as_server()
+            .headless()
+            .build();
+        let runner = game.clone();
+        let handle = thread::spawn(move || runner.run());
+        while !game.is_running() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let settings = game.get_settings();
+        let mut window = GameWindow::new(settings).unwrap();
+        settings.set("window.resizable", Boolean(false)).unwrap();
+        settings
+            .set("window.minimumSize", IVector2(Vector2i::new(100, 100)))
+            .unwrap();
+        window.swap();
+        assert!(!window.is_resizable());
+        assert_eq!(window.get_size_limits().0, 100);
+
+        game.stop();
+        handle.join().unwrap().unwrap();
+    });
 }
 
 fn window_integration() {